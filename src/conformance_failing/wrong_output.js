@@ -0,0 +1,3 @@
+print 1; // expect: Int(1)
+print 2; // expect: Int(3)
+print 4;
@@ -0,0 +1,20 @@
+var total = 0;
+for (n in range(0, 5, 1)) {
+    if (n == 3) {
+        continue;
+    }
+    var total = total + n;
+}
+print total; // expect: Int(7)
+
+fun countdown(n) {
+    while (n != 0) {
+        yield n;
+        var n = n - 1;
+    }
+}
+for (n in countdown(2)) {
+    print n;
+}
+// expect: Int(2)
+// expect: Int(1)
@@ -0,0 +1,13 @@
+try {
+    throw "boom";
+} catch (e) {
+    print e; // expect: String("boom")
+} finally {
+    print "cleanup"; // expect: String("cleanup")
+}
+
+try {
+    print [][0];
+} catch (e) {
+    print e.type; // expect: String("RuntimeError")
+}
@@ -0,0 +1,9 @@
+var xs = [1, 2, 3];
+print xs;        // expect: [Int(1), Int(2), Int(3)]
+print xs[-1];    // expect: Int(3)
+
+var ys = xs;
+push(ys, 4);
+print len(xs);   // expect: Int(4)
+print slice(xs, 1, 3); // expect: [Int(2), Int(3)]
+print join(["a", "b"], "-"); // expect: String("a-b")
@@ -0,0 +1,7 @@
+var ages = {"ann": 31, "bob": 27};
+ages["cid"] = 40;
+print keys(ages);     // expect: [String("ann"), String("bob"), String("cid")]
+print ages["bob"];    // expect: Int(27)
+print has(ages, "x"); // expect: Bool(false)
+print delete(ages, "ann"); // expect: Int(31)
+print len(ages);      // expect: Int(2)
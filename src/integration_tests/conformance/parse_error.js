@@ -0,0 +1,2 @@
+print "fine";
+var x = 1 // error at line 2
@@ -0,0 +1,4 @@
+var xs = [1, 2];
+print xs[0];  // expect: Int(1)
+print xs[2];  // expect runtime error: Index 2 is out of range for list of length 2
+print "You should NOT see this";
@@ -0,0 +1,4 @@
+fun fail() {
+    throw "bad"; // expect runtime error: Uncaught String("bad")
+}
+fail();
@@ -0,0 +1,8 @@
+print 3;         // expect: Int(3)
+print true;      // expect: Bool(true)
+print "text";    // expect: String("text")
+print nil;       // expect: Nil
+print 1 + 2;     // expect: Int(3)
+print 2 * 3 - 1; // expect: Int(5)
+print 1 == 1;    // expect: Bool(true)
+print 1 != 1;    // expect: Bool(false)
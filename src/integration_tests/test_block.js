@@ -0,0 +1,25 @@
+var x = 1;
+{
+    var y = 2;
+    print x + y; // expect: Int(3)
+}
+
+{
+    var x = x + 1;
+    {
+        var z = x + 1;
+        print z; // expect: Int(3)
+    };
+}
+print x; // expect: Int(2)
+
+var i = 0;
+while (i != 3) {
+    {
+        var i = i + 1;
+        if (i == 2) {
+            break;
+        };
+    }
+};
+print i; // expect: Int(2)
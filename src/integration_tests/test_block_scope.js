@@ -0,0 +1,4 @@
+{
+    var tmp = 3;
+}
+print tmp; // expect runtime error: Variable tmp is not in scope
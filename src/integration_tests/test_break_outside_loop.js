@@ -0,0 +1,5 @@
+while (true) {
+    fun escape() {
+        break; // error at line 3
+    };
+};
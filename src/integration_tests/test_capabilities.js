@@ -0,0 +1,14 @@
+write_file("target/lox_io_test/greeting.txt", "hello");
+print read_file("target/lox_io_test/greeting.txt"); // expect: String("hello")
+
+try {
+    read_file("Cargo.toml");
+} catch (err) {
+    print err.message; // expect: String("permission denied: fs.read of Cargo.toml outside of the allowed directories")
+}
+
+try {
+    print env("HOME");
+} catch (err) {
+    print err.message; // expect: String("permission denied: env")
+}
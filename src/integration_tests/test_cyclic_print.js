@@ -0,0 +1,8 @@
+var xs = [1];
+push(xs, xs);
+print xs; // expect: [Int(1), [...]]
+var m = {"a": 1};
+m["self"] = m;
+print m; // expect: {String("a"): Int(1), String("self"): {...}}
+print [m, m]; // expect: [{String("a"): Int(1), String("self"): {...}}, {String("a"): Int(1), String("self"): {...}}]
+print join(xs, ","); // expect runtime error: "[Int(1), [...]]" is not "a value".
@@ -0,0 +1,98 @@
+try {
+    throw "boom";
+    print "You should NOT see this";
+} catch (e) {
+    print e; // expect: String("boom")
+}
+
+try {
+    var xs = [1, 2];
+    print xs[5];
+} catch (e) {
+    print e.message; // expect: String("Index 5 is out of range for list of length 2")
+    print e.type; // expect: String("RuntimeError")
+    print e.line; // expect: Int(10)
+}
+
+try {
+    print 1 + "one";
+} catch (e) {
+    print e.type; // expect: String("LogicError")
+}
+
+fun risky(n) {
+    if (n == 0) {
+        throw {"code": 42};
+    }
+    return risky(n - 1);
+}
+
+try {
+    risky(3);
+} catch (e) {
+    print e["code"]; // expect: Int(42)
+}
+
+var log = [];
+fun cleanup() {
+    try {
+        return "from try";
+    } finally {
+        push(log, "cleaned");
+    }
+}
+print cleanup(); // expect: String("from try")
+print log; // expect: [String("cleaned")]
+
+fun override() {
+    try {
+        throw "lost";
+    } finally {
+        return "from finally";
+    }
+}
+print override(); // expect: String("from finally")
+
+try {
+    try {
+        throw "inner";
+    } finally {
+        push(log, "inner finally");
+    }
+} catch (e) {
+    print e; // expect: String("inner")
+}
+print log; // expect: [String("cleaned"), String("inner finally")]
+
+try {
+    try {
+        throw "first";
+    } catch (e) {
+        throw [e, "second"];
+    }
+} catch (e) {
+    print e; // expect: [String("first"), String("second")]
+}
+
+var i = 0;
+while (true) {
+    var i = i + 1;
+    try {
+        if (i == 3) {
+            break;
+        }
+        continue;
+    } finally {
+        push(log, i);
+    }
+}
+print log; // expect: [String("cleaned"), String("inner finally"), Int(1), Int(2), Int(3)]
+
+class error {
+    var message = "custom";
+}
+try {
+    throw error();
+} catch (e) {
+    print e.message; // expect: String("custom")
+}
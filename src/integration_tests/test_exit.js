@@ -0,0 +1,9 @@
+print "before"; // expect: String("before")
+try {
+    exit(3); // expect runtime error: Exited with code 3
+} catch (e) {
+    print "You should NOT see this";
+} finally {
+    print "You should NOT see this";
+}
+print "You should NOT see this";
@@ -2,21 +2,21 @@ fun foo() {
     return 0;
 };
 var x = foo();
-print foo();
+print foo(); // expect: Int(0)
 
 
 fun foo(bar) {
     return bar;
 };
 var x = foo(1);
-print foo(1);
+print foo(1); // expect: Int(1)
 
 
 fun foo(bar, baz) {
     return bar + baz;
 };
 var x = foo(1, 2);
-print foo(1, 2);
+print foo(1, 2); // expect: Int(3)
 
 
 fun foo(bar, baz) {
@@ -24,7 +24,7 @@ fun foo(bar, baz) {
     return bar + baz;
 };
 var x = foo(1, 2);
-print foo(1, 2);
+print foo(1, 2); // expect: Int(3)
 
 
 fun foo2(bar, baz) {
@@ -32,7 +32,7 @@ fun foo2(bar, baz) {
     return bar + baz;
 };
 var x = foo2(1, 2);
-print foo2(1, 2);
+print foo2(1, 2); // expect: Int(3)
 
 
 fun foo2(bar, baz) {
@@ -43,5 +43,5 @@ fun foo2(bar, baz) {
 };
 
 var x = foo2(1, 2);
-print foo2(1, 2);
+print foo2(1, 2); // expect: Int(2)
 
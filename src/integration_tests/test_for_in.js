@@ -0,0 +1,115 @@
+var total = 0;
+for (x in [1, 2, 3]) {
+    var total = total + x;
+}
+print total; // expect: Int(6)
+
+for (key in {"a": 1, "b": 2}) {
+    print key;
+}
+// expect: String("a")
+// expect: String("b")
+
+for (c in "hey") {
+    print c;
+}
+// expect: String("h")
+// expect: String("e")
+// expect: String("y")
+
+for (i in range(0, 10, 3)) {
+    print i;
+}
+// expect: Int(0)
+// expect: Int(3)
+// expect: Int(6)
+// expect: Int(9)
+
+for (i in range(3, 0, -1)) {
+    print i;
+}
+// expect: Int(3)
+// expect: Int(2)
+// expect: Int(1)
+
+var xs = [1, 2];
+for (x in xs) {
+    if (x == 1) {
+        push(xs, 3);
+    }
+    print x;
+}
+// expect: Int(1)
+// expect: Int(2)
+// expect: Int(3)
+
+outer: for (i in range(0, 3, 1)) {
+    for (j in range(0, 3, 1)) {
+        if (j == 1) {
+            continue outer;
+        }
+        if (i == 2) {
+            break outer;
+        }
+        print [i, j];
+    }
+}
+// expect: [Int(0), Int(0)]
+// expect: [Int(1), Int(0)]
+
+class countdown {
+    var n = 3;
+
+    fun iter() {
+        return this;
+    }
+
+    fun next() {
+        if (this.n == 0) {
+            return nil;
+        }
+        this.n = this.n - 1;
+        return this.n + 1;
+    }
+}
+
+for (n in countdown()) {
+    print n;
+}
+// expect: Int(3)
+// expect: Int(2)
+// expect: Int(1)
+for (n in countdown(2)) {
+    print n;
+}
+// expect: Int(2)
+// expect: Int(1)
+
+class bag {
+    var items = ["x", "y"];
+
+    fun iter() {
+        return iter(this.items);
+    }
+}
+
+for (item in bag()) {
+    print item;
+}
+// expect: String("x")
+// expect: String("y")
+
+var it = iter([7, 8]);
+print it.next(); // expect: Int(7)
+print it.next(); // expect: Int(8)
+print it.next(); // expect: Nil
+
+fun find(items, target) {
+    for (item in items) {
+        if (item == target) {
+            return true;
+        }
+    }
+    return false;
+}
+print find(["a", "b"], "b"); // expect: Bool(true)
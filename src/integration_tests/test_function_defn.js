@@ -29,4 +29,4 @@ fun foo2(bar, baz) {
 
 fun fib(n) { if (n == 0) { return n; }; if (n==1) { return n; }; return fib(n-1) + fib(n-2); };
 
-print fib(20);
\ No newline at end of file
+print fib(20); // expect: Int(6765)
\ No newline at end of file
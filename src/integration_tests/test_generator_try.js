@@ -0,0 +1,89 @@
+var log = [];
+
+fun guarded(items) {
+    for (item in items) {
+        try {
+            if (item == 0) {
+                throw "zero";
+            }
+            yield item;
+        } catch (e) {
+            yield e;
+        } finally {
+            push(log, item);
+        }
+    }
+}
+
+for (x in guarded([1, 0, 2])) {
+    print x;
+}
+// expect: Int(1)
+// expect: String("zero")
+// expect: Int(2)
+print log; // expect: [Int(1), Int(0), Int(2)]
+
+fun cleaned_up() {
+    try {
+        yield 1;
+        return 0;
+        yield "You should NOT see this";
+    } finally {
+        yield "finally";
+    }
+}
+
+for (x in cleaned_up()) {
+    print x;
+}
+// expect: Int(1)
+// expect: String("finally")
+
+fun first_of(items) {
+    while (true) {
+        try {
+            for (item in items) {
+                try {
+                    yield item;
+                    break;
+                } finally {
+                    push(log, "inner");
+                }
+            }
+            break;
+        } finally {
+            push(log, "outer");
+        }
+    }
+    yield "after";
+}
+
+for (x in first_of(["a", "b"])) {
+    print x;
+}
+// expect: String("a")
+// expect: String("after")
+print log; // expect: [Int(1), Int(0), Int(2), String("inner"), String("outer")]
+
+fun failing() {
+    try {
+        yield 1;
+        print 1 + "one";
+    } catch (e) {
+        yield e.type;
+        throw "again";
+    } finally {
+        push(log, "failing");
+    }
+}
+
+try {
+    for (x in failing()) {
+        print x;
+    }
+    // expect: Int(1)
+    // expect: String("LogicError")
+} catch (e) {
+    print e; // expect: String("again")
+}
+print log; // expect: [Int(1), Int(0), Int(2), String("inner"), String("outer"), String("failing")]
@@ -0,0 +1,111 @@
+fun count_to(n) {
+    var i = 0;
+    while (i != n) {
+        var i = i + 1;
+        yield i;
+    }
+}
+
+for (x in count_to(3)) {
+    print x;
+}
+// expect: Int(1)
+// expect: Int(2)
+// expect: Int(3)
+
+var gen = count_to(2);
+print gen.next(); // expect: Int(1)
+print gen.next(); // expect: Int(2)
+print gen.next(); // expect: Nil
+print gen.next(); // expect: Nil
+
+fun evens(items) {
+    for (item in items) {
+        if (mod(item, 2) == 0) {
+            yield item;
+        }
+    }
+    yield "done";
+    return 0;
+    yield "You should NOT see this";
+}
+
+for (x in evens([1, 2, 3, 4, 5, 6])) {
+    print x;
+}
+// expect: Int(2)
+// expect: Int(4)
+// expect: Int(6)
+// expect: String("done")
+
+fun naturals() {
+    var n = 0;
+    while (true) {
+        yield n;
+        var n = n + 1;
+    }
+}
+
+for (n in naturals()) {
+    if (n == 1) {
+        continue;
+    }
+    if (n == 4) {
+        break;
+    }
+    print n;
+}
+// expect: Int(0)
+// expect: Int(2)
+// expect: Int(3)
+
+fun pairs() {
+    outer: for (i in range(0, 3, 1)) {
+        for (j in range(0, 3, 1)) {
+            if (j == 2) {
+                continue outer;
+            }
+            if (i == 2) {
+                break outer;
+            }
+            {
+                var pair = [i, j];
+                yield pair;
+            }
+        }
+    }
+}
+
+for (p in pairs()) {
+    print p;
+}
+// expect: [Int(0), Int(0)]
+// expect: [Int(0), Int(1)]
+// expect: [Int(1), Int(0)]
+// expect: [Int(1), Int(1)]
+
+class tree {
+    var items = [3, 1, 2];
+
+    fun iter() {
+        for (item in this.items) {
+            yield item * 10;
+        }
+    }
+}
+
+for (x in tree()) {
+    print x;
+}
+// expect: Int(30)
+// expect: Int(10)
+// expect: Int(20)
+
+fun take(gen, count) {
+    var taken = [];
+    while (len(taken) != count) {
+        push(taken, gen.next());
+    }
+    return taken;
+}
+print take(naturals(), 5); // expect: [Int(0), Int(1), Int(2), Int(3), Int(4)]
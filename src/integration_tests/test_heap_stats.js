@@ -0,0 +1,17 @@
+class point {
+    var x = 0;
+    var y = 0;
+}
+
+var numbers = [1, 2, 3];
+var nested = {"inner": [4, 5]};
+var alias = numbers;
+var origin = point();
+fun double(n) {
+    return n * 2;
+}
+
+var stats = heap_stats();
+print stats; // expect: {String("kilobytes"): Int(4), String("strings"): Int(1), String("lists"): Int(2), String("maps"): Int(1), String("objects"): Int(1), String("functions"): Int(1), String("classes"): Int(1), String("iterators"): Int(0), String("generators"): Int(0), String("host_objects"): Int(1)}
+print stats["lists"]; // expect: Int(2)
+print stats["maps"]; // expect: Int(1)
@@ -0,0 +1,18 @@
+print counter; // expect: Counter(1)
+counter.increment(2);
+print counter.count; // expect: Int(3)
+
+counter.count = 10;
+print counter.count; // expect: Int(10)
+
+try {
+    counter.missing();
+} catch (err) {
+    print err.message; // expect: String("Counter has no method missing")
+}
+
+try {
+    counter.label = "x";
+} catch (err) {
+    print err.message; // expect: String("Cannot set property label on Counter")
+}
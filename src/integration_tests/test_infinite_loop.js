@@ -0,0 +1,9 @@
+var caught = false;
+try {
+    while (true) {} // error at line 3
+} catch (err) {
+    var caught = true;
+} finally {
+    var caught = true;
+}
+print caught;
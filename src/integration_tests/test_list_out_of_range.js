@@ -0,0 +1,2 @@
+var xs = [1, 2, 3];
+print xs[3]; // expect runtime error: Index 3 is out of range for list of length 3
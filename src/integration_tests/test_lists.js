@@ -0,0 +1,37 @@
+var xs = [1, 2, 3];
+print xs; // expect: [Int(1), Int(2), Int(3)]
+print xs[0]; // expect: Int(1)
+print xs[-1]; // expect: Int(3)
+print -xs[1]; // expect: Int(-2)
+
+xs[1] = 20;
+xs[-1] = 30;
+print xs; // expect: [Int(1), Int(20), Int(30)]
+
+var ys = xs;
+push(ys, 4);
+print len(xs); // expect: Int(4)
+print pop(xs); // expect: Int(4)
+print xs; // expect: [Int(1), Int(20), Int(30)]
+
+insert(xs, 0, 0);
+insert(xs, -1, 25);
+print xs; // expect: [Int(0), Int(1), Int(20), Int(25), Int(30)]
+print remove(xs, 1); // expect: Int(1)
+print slice(xs, 1, -1); // expect: [Int(20), Int(25)]
+print slice(xs, -10, 10); // expect: [Int(0), Int(20), Int(25), Int(30)]
+print contains(xs, 25); // expect: Bool(true)
+print contains(xs, 7); // expect: Bool(false)
+
+var nested = [[1, 2], [3, [4, 5]], []];
+print nested[1][1][0]; // expect: Int(4)
+print len(nested[2]); // expect: Int(0)
+
+var words = ["a", "b", "c"];
+print join(words, ", "); // expect: String("a, b, c")
+
+fun first(list) {
+    return list[0];
+}
+print first([7, 8]); // expect: Int(7)
+print [9, 10][1]; // expect: Int(10)
@@ -0,0 +1,49 @@
+var i = 0;
+var sum = 0;
+while (i != 10) {
+    var i = i + 1;
+    if (i == 3) {
+        continue;
+    };
+    if (i == 8) {
+        break;
+    };
+    var sum = sum + i;
+};
+print sum; // expect: Int(25)
+
+
+outer: while (true) {
+    while (true) {
+        break outer;
+    };
+    print "You should NOT see this";
+};
+
+
+var row = 0;
+var visited = 0;
+rows: while (row != 3) {
+    var row = row + 1;
+    var col = 0;
+    while (col != 3) {
+        var col = col + 1;
+        if (col == 2) {
+            continue rows;
+        };
+        var visited = visited + 1;
+    };
+};
+print visited; // expect: Int(3)
+
+
+fun first_at(limit) {
+    var n = 0;
+    while (true) {
+        var n = n + 1;
+        if (n == limit) {
+            return n;
+        };
+    };
+};
+print first_at(4); // expect: Int(4)
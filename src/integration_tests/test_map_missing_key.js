@@ -0,0 +1,2 @@
+var m = {"a": 1};
+print m["b"]; // expect runtime error: Key String("b") is not in the map
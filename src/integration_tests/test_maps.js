@@ -0,0 +1,35 @@
+var ages = {"bob": 31, "alice": 27};
+print ages; // expect: {String("bob"): Int(31), String("alice"): Int(27)}
+print ages["alice"]; // expect: Int(27)
+
+ages["carol"] = 45;
+ages["bob"] = 32;
+print ages; // expect: {String("bob"): Int(32), String("alice"): Int(27), String("carol"): Int(45)}
+print len(ages); // expect: Int(3)
+
+print has(ages, "bob"); // expect: Bool(true)
+print delete(ages, "bob"); // expect: Int(32)
+print has(ages, "bob"); // expect: Bool(false)
+print keys(ages); // expect: [String("alice"), String("carol")]
+print values(ages); // expect: [Int(27), Int(45)]
+
+var mixed = {1: "one", true: "yes", "k": [1, 2]};
+print mixed[1]; // expect: String("one")
+print mixed[true]; // expect: String("yes")
+print mixed["k"][1]; // expect: Int(2)
+
+var empty = {};
+empty[0] = {"nested": 1};
+print empty[0]["nested"]; // expect: Int(1)
+
+var alias = empty;
+alias[1] = 2;
+print len(empty); // expect: Int(2)
+
+{
+    var key = "x";
+    var lookup = {key: 5};
+    print lookup["x"]; // expect: Int(5)
+}
+
+{ outer: while (true) { break outer; } }
@@ -0,0 +1,3 @@
+var x = 1;
+var y = x + 2 // error at line 2
+print y;
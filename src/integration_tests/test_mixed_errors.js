@@ -0,0 +1,6 @@
+var a = 1 @ 2; // error at line 1
+print a;
+var b = ;
+print #;
+var c = 3
+print c;
@@ -0,0 +1,12 @@
+print mod(7, 3); // expect: Int(1)
+try {
+    mod(1, 0);
+} catch (err) {
+    print err.message; // expect: String("Division by zero in mod of 1 and 0")
+}
+try {
+    mod(0 - 32767 - 1, 0 - 1);
+} catch (err) {
+    print err.message; // expect: String("Integer overflow in mod of -32768 and -1")
+}
+print mod(0 - 32767 - 1, 1); // expect: Int(0)
@@ -0,0 +1,12 @@
+print mod(7, 3); // expect: Int(1)
+print double(21); // expect: Int(42)
+print apply(double, 4); // expect: Int(8)
+
+var renamed = mod;
+print renamed(9, 4); // expect: Int(1)
+
+try {
+    double(1, 2);
+} catch (err) {
+    print err.message; // expect: String("double expects 1 arguments, but got 2")
+}
@@ -0,0 +1,18 @@
+var xs = [1, 2];
+var ys = xs;
+print xs == ys; // expect: Bool(true)
+print xs == [1, 2]; // expect: Bool(false)
+print xs != [1, 2]; // expect: Bool(true)
+
+var m = {"a": 1};
+print m == m; // expect: Bool(true)
+print m == {"a": 1}; // expect: Bool(false)
+
+class point {
+    var x = 0;
+}
+var p = point();
+print p == p; // expect: Bool(true)
+print p == point(); // expect: Bool(false)
+print p == nil; // expect: Bool(false)
+print nil != xs; // expect: Bool(true)
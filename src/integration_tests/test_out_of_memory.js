@@ -0,0 +1,4 @@
+var items = [];
+while (true) {
+    push(items, [1, 2, 3, 4, 5, 6, 7, 8]); // error at line 3
+}
@@ -0,0 +1,4 @@
+var entries = {};
+for (i in range(0, 30000, 1)) {
+    entries[i] = "some text which takes up room"; // error at line 3
+}
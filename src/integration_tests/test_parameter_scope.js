@@ -0,0 +1,6 @@
+var n = 1;
+fun show(n) {
+    print n; // expect: Int(2)
+}
+show(2);
+print n; // expect: Int(1)
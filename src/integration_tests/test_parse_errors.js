@@ -0,0 +1,10 @@
+var a = 1 2; // error at line 1
+print a;
+var b = ;
+fun f(x) {
+    print x +;
+    return x;
+}
+print f(1) )
+var c = 3;
+print c;
@@ -0,0 +1 @@
+write_file("target/lox_io_test/denied.txt", "nope");
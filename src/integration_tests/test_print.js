@@ -1,15 +1,15 @@
-print "fsadf";
-print 3;
-print true;
+print "fsadf"; // expect: String("fsadf")
+print 3; // expect: Int(3)
+print true; // expect: Bool(true)
 
 var x = 3;
-print x;
+print x; // expect: Int(3)
 
 var y = "fsdaf";
-print y;
+print y; // expect: String("fsdaf")
 
 var z = false;
-print z;
+print z; // expect: Bool(false)
 
-print x + 3;
-print x - 3232;
\ No newline at end of file
+print x + 3; // expect: Int(6)
+print x - 3232; // expect: Int(-3229)
\ No newline at end of file
@@ -0,0 +1,5 @@
+var a = 1 @ 2; // error at line 1
+var b = 12ab;
+print #;
+var c = 99999;
+var d = "never closed;
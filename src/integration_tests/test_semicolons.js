@@ -0,0 +1,24 @@
+fun add(a, b) {
+    return a + b;
+}
+
+class point {
+    var x = 0;
+    var y = 0;
+    fun norm() {
+        return x + y;
+    }
+}
+
+var i = 0;
+while (i != 3) {
+    var i = add(i, 1);
+    if (i == 2) {
+        print i; // expect: Int(2)
+    }
+}
+
+{
+    print add(i, 1); // expect: Int(4)
+}
+print i; // expect: Int(3)
@@ -0,0 +1,20 @@
+fun forever(n) {
+    return forever(n + 1);
+}
+
+var caught = false;
+try {
+    forever(0);
+} catch (err) {
+    var caught = true;
+    print err.message; // expect: String("Stack overflow: more than 5000 nested calls while calling forever")
+}
+print caught; // expect: Bool(true)
+
+fun deep(n) {
+    if (n == 0) {
+        return 0;
+    }
+    return deep(n - 1) + 1;
+}
+print deep(2000); // expect: Int(2000)
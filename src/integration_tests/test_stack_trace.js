@@ -0,0 +1,12 @@
+fun countdown(n) {
+    if (n == 0) {
+        return [][0]; // expect runtime error: Index 0 is out of range for list of length 0
+    }
+    return countdown(n - 1);
+}
+
+fun start() {
+    return countdown(2);
+}
+
+start();
@@ -0,0 +1,4 @@
+fun fail() {
+    throw "nobody catches this"; // expect runtime error: Uncaught String("nobody catches this")
+}
+fail();
@@ -0,0 +1,5 @@
+fun forever(n) {
+    return forever(n + 1); // expect runtime error: Stack overflow: more than 5000 nested calls while calling forever
+}
+
+forever(0);
@@ -1,21 +1,33 @@
 //! The interpreter. Can either run a REPL or read a file with lox program.
 
-use crate::interpreter::execute::Executor;
+use crate::interpreter::execute::{
+    Capabilities, Capability, Ctx, Executor, HeapStats, LoxObj, NativeFunction,
+};
 use crate::interpreter::parser::visitor::Visitor;
 use std::fmt;
 use std::io;
 use std::io::Write;
+use std::sync::atomic::AtomicBool;
+use std::sync::Arc;
+use std::time::Duration;
 
-use errors::LoxError;
+use errors::source_map::SourceMap;
+use errors::span::{FileId, Span};
+use errors::{ErrBuilder, ErrType, ErrorFormat, LoxError, LoxResult};
+use parser::structure::{Program, Statement, Stmt};
+use value::Value;
 use parser::Parser;
 use scanner::Scanner;
 
+pub mod conformance;
 pub mod errors;
 pub mod execute;
+pub mod output;
 pub mod parser;
 pub mod readers;
 pub mod scanner;
 pub mod tokens;
+pub mod value;
 
 fn print_with_flush<T>(text: T)
 where
@@ -25,18 +37,138 @@ where
     io::stdout().flush().expect("Flush failed!");
 }
 
+/// Native stack size for running the interpreter, enough for `DEFAULT_MAX_CALL_DEPTH` nested calls.
+pub const STACK_SIZE: usize = 1 << 30;
+
+/// Runs F on a new thread with a stack of `STACK_SIZE`. Lox calls grow the stack on their own
+/// when it runs low, so this is not needed to hit the call depth limit instead of overflowing the
+/// native stack, but it saves switching between stack segments in deeply recursive programs.
+pub fn with_large_stack<F, T>(f: F) -> T
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    std::thread::Builder::new()
+        .stack_size(STACK_SIZE)
+        .spawn(f)
+        .expect("Could not spawn interpreter thread")
+        .join()
+        .unwrap_or_else(|panic| std::panic::resume_unwind(panic))
+}
+
 pub struct LoxInterpreter {
     executor: Executor,
+    sources: SourceMap,
+    /// Source of the last `eval` or REPL line if it defined no functions, dropped on the next one.
+    transient_source: Option<FileId>,
+    error_format: ErrorFormat,
+    /// Where errors are reported, the output if not set.
+    diagnostics: Option<Box<dyn Write>>,
 }
 
 impl LoxInterpreter {
     pub fn new() -> Self {
         LoxInterpreter {
             executor: Executor::new(),
+            sources: SourceMap::new(),
+            transient_source: None,
+            error_format: ErrorFormat::Human,
+            diagnostics: None,
         }
     }
 
-    pub fn run_prompt(&mut self) {
+    /// Sources which errors and defined functions point into. Sources of `eval` calls and REPL
+    /// lines which define no functions are only kept until the next one runs.
+    pub fn sources(&self) -> &SourceMap {
+        &self.sources
+    }
+
+    /// Calls nested deeper than `depth` raise a stack overflow error.
+    pub fn with_max_call_depth(mut self, depth: usize) -> Self {
+        self.executor.max_call_depth = depth;
+        self
+    }
+
+    /// Makes `fun` callable from Lox as `name`, taking exactly `arity` arguments. Replaces any
+    /// function or global of the same name.
+    pub fn register_native<F>(&mut self, name: &str, arity: usize, fun: F)
+    where
+        F: Fn(&mut Ctx, Vec<LoxObj>) -> LoxResult<LoxObj> + 'static,
+    {
+        self.executor.register(NativeFunction::new(name, arity, fun));
+    }
+
+    /// Like `register_native`, but calls are refused with a runtime error unless `capability` is
+    /// granted, like calls of built-in functions with side effects.
+    pub fn register_native_with_capability<F>(
+        &mut self,
+        name: &str,
+        arity: usize,
+        capability: Capability,
+        fun: F,
+    ) where
+        F: Fn(&mut Ctx, Vec<LoxObj>) -> LoxResult<LoxObj> + 'static,
+    {
+        self.executor.register(NativeFunction::new(name, arity, fun).requires(capability));
+    }
+
+    /// Sends everything printed by scripts to `output`, as well as error reports unless they have
+    /// a sink of their own, see `with_diagnostics`.
+    pub fn with_output(mut self, output: impl Write + 'static) -> Self {
+        self.executor.output = Box::new(output);
+        self
+    }
+
+    /// Reports errors in `format`.
+    pub fn with_error_format(mut self, format: ErrorFormat) -> Self {
+        self.error_format = format;
+        self
+    }
+
+    /// Reports errors to `diagnostics` instead of the output, e.g. so JSON reports can be read
+    /// apart from what the script prints.
+    pub fn with_diagnostics(mut self, diagnostics: impl Write + 'static) -> Self {
+        self.diagnostics = Some(Box::new(diagnostics));
+        self
+    }
+
+    /// Built-in functions with side effects can only be called if their capability is granted.
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.executor.capabilities = capabilities;
+        self
+    }
+
+    /// Live values may take at most about `bytes` of memory.
+    pub fn with_memory_limit(mut self, bytes: usize) -> Self {
+        self.executor.heap.max_bytes = Some(bytes);
+        self
+    }
+
+    /// Live values by kind, with their approximate size.
+    pub fn heap_stats(&self) -> HeapStats {
+        self.executor.heap_stats()
+    }
+
+    /// Each run may take at most `steps` loop iterations and calls.
+    pub fn with_step_limit(mut self, steps: u64) -> Self {
+        self.executor.limits.max_steps = Some(steps);
+        self
+    }
+
+    /// Each run may take at most `timeout` of wall-clock time.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.executor.limits.timeout = Some(timeout);
+        self
+    }
+
+    /// Setting `flag`, e.g. from another thread, aborts the running program.
+    pub fn with_cancel_flag(mut self, flag: Arc<AtomicBool>) -> Self {
+        self.executor.limits.cancel = Some(flag);
+        self
+    }
+
+    /// Reads and runs lines from stdin. Returns the `Exit` error once a line calls `exit`.
+    pub fn run_prompt(&mut self) -> Option<LoxError> {
         let mut buffer = String::new();
         let stdin = io::stdin();
         loop {
@@ -45,60 +177,181 @@ impl LoxInterpreter {
 
             match next_line {
                 Ok(0) => {}
-                Ok(_) => self.interpret_line_and_respond(buffer.clone()),
+                Ok(_) => {
+                    if let Some(exit) = self.interpret_line_and_respond(buffer.clone()) {
+                        return Some(exit);
+                    }
+                }
                 Err(err) => {
                     self.handle_err(&err);
-                    break;
+                    return None;
                 }
             };
             buffer.clear();
         }
     }
 
-    fn interpret_line_and_respond(&mut self, mut line: String) {
+    fn interpret_line_and_respond(&mut self, mut line: String) -> Option<LoxError> {
         line = line.replace("\\n", "\n");
         line = line.trim().to_string();
 
         if line.ends_with('\r') {}
 
-        let response = self.run(line.clone());
+        let response = self.run(line);
         match response {
-            Ok(_) => {}
+            Ok(_) => None,
+            Err(err) if matches!(err.err_type, ErrType::Exit(_)) => Some(err),
             Err(error_message) => {
-                println!("{}", error_message.generate_err_msg(&line));
+                self.report(&error_message);
+                None
             }
         }
     }
 
     pub fn run_file(&mut self, path: &String) -> Option<LoxError> {
-        let content = std::fs::read_to_string(path).expect("Something went wrong reading the file");
-
-        let scanner_output = Scanner::new(content.clone()).scan();
-        if let Err(err) = scanner_output {
-            println!("{}", err.generate_err_msg(&content));
-            return Some(err);
-        }
+        let content = match std::fs::read_to_string(path) {
+            Ok(content) => content,
+            Err(err) => {
+                let err = ErrBuilder::new()
+                    .of_type(ErrType::InterpreterError)
+                    .without_pos()
+                    .with_message(format!("Could not read {}: {}", path, err))
+                    .build();
+                self.report(&err);
+                return Some(err);
+            }
+        };
 
-        let parser_output = Parser::new(scanner_output.unwrap()).parse();
-        if let Err(err) = parser_output {
-            println!("{}", err.generate_err_msg(&content));
-            return Some(err);
+        let file_id = self.sources.add(path.clone(), content.clone());
+        let mut scanner_output = Scanner::new(content).in_file(file_id).scan();
+        // the scanner leaves error tokens in place of what it couldn't read, so the parser can
+        // still find the syntax errors around them
+        let mut errors = std::mem::take(&mut scanner_output.errors);
+        let parser_output = Parser::new(scanner_output).parse();
+        errors.extend(parser_output.errors);
+        if !errors.is_empty() {
+            errors.sort_by_key(|err| (err.pos().line, err.pos().line_pos));
+            return self.report_all(errors);
         }
 
-        let executor_output = self.executor.visit(&parser_output.unwrap());
+        self.executor.limits.start();
+        let executor_output = self.executor.visit(&parser_output.program);
         if let Err(err) = executor_output {
-            println!("{}", err.generate_err_msg(&content));
+            self.report(&err);
             return Some(err);
         }
 
         None
     }
 
+    /// Runs `source` and returns the value of its last statement if that is an expression, `nil`
+    /// otherwise. Unlike `run_file`, errors are only returned, not printed.
+    pub fn eval(&mut self, source: &str) -> Result<Value, LoxError> {
+        let file_id = self.add_transient_source("<eval>", source);
+        let tokens = Scanner::new(source.to_string()).in_file(file_id).scan().into_result()?;
+        let mut program = Parser::new(tokens).parse().into_result()?;
+        self.keep_if_defining(&program);
+        let last_expr = match program.last() {
+            Some(Stmt { statement: Statement::Expr(_), .. }) => match program.pop() {
+                Some(Stmt { statement: Statement::Expr(expr), .. }) => Some(expr),
+                _ => None,
+            },
+            _ => None,
+        };
+
+        self.executor.limits.start();
+        let evaluated = self.executor.visit(&program)?;
+        if let Some(returned) = evaluated.returned {
+            return Ok(Value::from_lox(&returned));
+        }
+        match last_expr {
+            Some(expr) => Ok(Value::from_lox(&self.executor.visit(&expr)?)),
+            None => Ok(Value::Nil),
+        }
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<Value> {
+        self.executor.state.get_global(name).map(|obj| Value::from_lox(&obj))
+    }
+
+    /// Fails if `value` can't be represented in Lox, e.g. an integer which is too large.
+    pub fn set_global(&mut self, name: &str, value: impl Into<Value>) -> Result<(), LoxError> {
+        let obj = value.into().to_lox()?;
+        self.executor.state.bind_global(name.to_string(), obj);
+        Ok(())
+    }
+
+    /// Calls the global function `name`, which may be a Lox function, a class or a native function.
+    pub fn call_function(&mut self, name: &str, args: Vec<Value>) -> Result<Value, LoxError> {
+        let host = Span::none();
+        let func = match self.executor.state.get_global(name) {
+            Some(func) => func,
+            None => {
+                return ErrBuilder::new()
+                    .of_type(ErrType::RuntimeError)
+                    .at(host)
+                    .with_message(format!("No global function {}", name))
+                    .to_result()
+            }
+        };
+        let args: LoxResult<Vec<_>> = args.iter().map(|arg| arg.to_lox()).collect();
+
+        self.executor.limits.start();
+        let result = self.executor.call(&func, args?, host)?;
+        Ok(Value::from_lox(&result))
+    }
+
+    /// Writes the diagnostic for `err` to the diagnostics sink, or to the output next to what the
+    /// script printed.
+    fn report(&mut self, err: &LoxError) {
+        if let ErrType::Exit(_) = err.err_type {
+            return;
+        }
+        let msg = match self.error_format {
+            ErrorFormat::Human => err.generate_err_msg(&self.sources),
+            ErrorFormat::Json => err.to_json(&self.sources),
+        };
+        let sink = match &mut self.diagnostics {
+            Some(diagnostics) => diagnostics,
+            None => &mut self.executor.output,
+        };
+        // there's nowhere left to report a failing sink to
+        let _ = writeln!(sink, "{}", msg);
+    }
+
+    /// Reports each of `errors`, and returns the first one.
+    fn report_all(&mut self, errors: Vec<LoxError>) -> Option<LoxError> {
+        for err in &errors {
+            self.report(err);
+        }
+        errors.into_iter().next()
+    }
+
+    /// Adds the source of an `eval` call or REPL line, dropping the previous one unless it was kept.
+    fn add_transient_source(&mut self, name: &str, source: &str) -> FileId {
+        if let Some(previous) = self.transient_source.take() {
+            self.sources.remove(previous);
+        }
+        let file_id = self.sources.add(name, source);
+        self.transient_source = Some(file_id);
+        file_id
+    }
+
+    /// Functions keep pointing into their source, so it has to stay for their errors.
+    fn keep_if_defining(&mut self, program: &Program) {
+        if program.iter().any(|stmt| stmt.statement.defines_functions()) {
+            self.transient_source = None;
+        }
+    }
+
     pub fn handle_err(&self, _err: &std::io::Error) {}
 
     fn run(&mut self, statement: String) -> Result<String, LoxError> {
-        let scanner_output = Scanner::new(statement.clone()).scan()?;
-        let parser_output = Parser::new(scanner_output).parse()?;
+        let file_id = self.add_transient_source("<repl>", &statement);
+        let scanner_output = Scanner::new(statement).in_file(file_id).scan().into_result()?;
+        let parser_output = Parser::new(scanner_output).parse().into_result()?;
+        self.keep_if_defining(&parser_output);
+        self.executor.limits.start();
         self.executor.visit(&parser_output)?;
 
         Ok(format!("Ok!"))
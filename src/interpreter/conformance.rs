@@ -0,0 +1,195 @@
+//! Runs annotated scripts and checks what they print, the format used by the standard Lox test
+//! suite. Scripts state what they should do in comments:
+//!
+//! ```text
+//! print 1;           // expect: Int(1)
+//! print [][0];       // expect runtime error: Index 0 is out of range for list of length 0
+//! var x = 1          // error at line 3
+//! ```
+//!
+//! `expect:` gives the next line of output. `expect runtime error:` gives the message of an error
+//! raised on the line of the comment, `error at line N` an error of any kind raised on line N.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::interpreter::errors::LoxError;
+use crate::interpreter::output::OutputBuffer;
+use crate::interpreter::LoxInterpreter;
+
+/// Scripts running longer than this are treated as hanging.
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Expectations {
+    pub output: Vec<String>,
+    pub error: Option<ExpectedError>,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ExpectedError {
+    Runtime { line: usize, msg: String },
+    AtLine(usize),
+}
+
+impl Expectations {
+    pub fn parse(source: &str) -> Self {
+        let mut expectations = Expectations::default();
+        for (i, line) in source.lines().enumerate() {
+            let comment = match line.find("//") {
+                Some(start) => line[start + 2..].trim(),
+                None => continue,
+            };
+
+            if let Some(output) = comment.strip_prefix("expect:") {
+                expectations.output.push(output.trim().to_string());
+            } else if let Some(msg) = comment.strip_prefix("expect runtime error:") {
+                expectations.error = Some(ExpectedError::Runtime {
+                    line: i + 1,
+                    msg: msg.trim().to_string(),
+                });
+            } else if let Some(line) = comment.strip_prefix("error at line") {
+                if let Ok(line) = line.trim().parse() {
+                    expectations.error = Some(ExpectedError::AtLine(line));
+                }
+            }
+        }
+        expectations
+    }
+
+    /// Lines describing how the run differed from the expectations, empty if it didn't.
+    pub fn diff(&self, output: &str, error: Option<&LoxError>) -> Vec<String> {
+        let mut diff = Vec::new();
+        let actual: Vec<&str> = output.lines().collect();
+        for i in 0..self.output.len().max(actual.len()) {
+            match (self.output.get(i), actual.get(i)) {
+                (Some(expected), Some(actual)) if expected == actual => {}
+                (expected, actual) => {
+                    diff.push(format!("output line {}:", i + 1));
+                    if let Some(expected) = expected {
+                        diff.push(format!("- {}", expected));
+                    }
+                    if let Some(actual) = actual {
+                        diff.push(format!("+ {}", actual));
+                    }
+                }
+            }
+        }
+
+        // messages may go on with details about what the interpreter was doing
+        let error = error.map(|err| (err.pos().line as usize + 1, err.msg.lines().next().unwrap_or("")));
+        match (&self.error, error) {
+            (None, None) => {}
+            (Some(ExpectedError::Runtime { line, msg }), Some((actual_line, actual_msg)))
+                if *line == actual_line && msg == actual_msg => {}
+            (Some(ExpectedError::AtLine(line)), Some((actual_line, _))) if *line == actual_line => {}
+            (expected, actual) => {
+                diff.push("error:".to_string());
+                diff.push(match expected {
+                    Some(ExpectedError::Runtime { line, msg }) => format!("- line {}: {}", line, msg),
+                    Some(ExpectedError::AtLine(line)) => format!("- line {}", line),
+                    None => "- no error".to_string(),
+                });
+                diff.push(match actual {
+                    Some((line, msg)) => format!("+ line {}: {}", line, msg),
+                    None => "+ no error".to_string(),
+                });
+            }
+        }
+        diff
+    }
+}
+
+pub struct Failure {
+    pub path: PathBuf,
+    pub diff: Vec<String>,
+}
+
+impl fmt::Display for Failure {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "FAIL {}", self.path.display())?;
+        for line in &self.diff {
+            writeln!(f, "    {}", line)?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Default)]
+pub struct Report {
+    pub passed: usize,
+    pub failures: Vec<Failure>,
+}
+
+impl fmt::Display for Report {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for failure in &self.failures {
+            write!(f, "{}", failure)?;
+        }
+        write!(f, "{} passed, {} failed", self.passed, self.failures.len())
+    }
+}
+
+/// Prepares the interpreter for a script, e.g. registers the natives it calls.
+pub type Setup<'a> = &'a dyn Fn(LoxInterpreter) -> LoxInterpreter;
+
+/// Runs the script at `path` in a fresh interpreter. Returns how it differed from its
+/// expectations, if it did.
+pub fn run_script(path: &Path) -> io::Result<Option<Failure>> {
+    run_script_with(path, &|interpreter| interpreter)
+}
+
+/// Like `run_script`, but the interpreter goes through `setup` first.
+pub fn run_script_with(path: &Path, setup: Setup) -> io::Result<Option<Failure>> {
+    let source = fs::read_to_string(path)?;
+    let expectations = Expectations::parse(&source);
+
+    let output = OutputBuffer::new();
+    let interpreter = LoxInterpreter::new()
+        .with_output(output.clone())
+        .with_timeout(TIMEOUT);
+    let error = setup(interpreter).eval(&source).err();
+
+    let diff = expectations.diff(&output.contents(), error.as_ref());
+    match diff.is_empty() {
+        true => Ok(None),
+        false => Ok(Some(Failure {
+            path: path.to_path_buf(),
+            diff,
+        })),
+    }
+}
+
+/// Runs every `.js` and `.lox` script in `dir` and its subdirectories, in alphabetical order.
+pub fn run_dir(dir: &Path) -> io::Result<Report> {
+    run_dir_with(dir, &|interpreter| interpreter)
+}
+
+/// Like `run_dir`, but each interpreter goes through `setup` first.
+pub fn run_dir_with(dir: &Path, setup: Setup) -> io::Result<Report> {
+    let mut report = Report::default();
+    for path in scripts_in(dir)? {
+        match run_script_with(&path, setup)? {
+            Some(failure) => report.failures.push(failure),
+            None => report.passed += 1,
+        }
+    }
+    Ok(report)
+}
+
+fn scripts_in(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    let mut scripts = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.is_dir() {
+            scripts.extend(scripts_in(&path)?);
+        } else if matches!(path.extension().and_then(|ext| ext.to_str()), Some("js" | "lox")) {
+            scripts.push(path);
+        }
+    }
+    scripts.sort();
+    Ok(scripts)
+}
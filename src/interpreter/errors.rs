@@ -1,8 +1,14 @@
 //! Provides `LoxError` class used for scanning/parsing/runtime errors as well as a builder utility class.
 
 use crate::interpreter::errors::position::Position;
+use crate::interpreter::errors::source_map::SourceMap;
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::execute::LoxObj;
 
+pub mod json;
 pub mod position;
+pub mod source_map;
+pub mod span;
 
 pub type LoxResult<A> = Result<A, LoxError>;
 
@@ -15,120 +21,249 @@ pub enum ErrType {
     LogicError,
     RuntimeError,
     InterpreterError,
+    /// Value thrown with `throw` which was not caught.
+    Exception,
+    /// Execution ran over its step budget. Like the other aborts, can't be caught.
+    StepLimit,
+    /// Execution ran over its timeout.
+    Timeout,
+    /// Execution was cancelled by the host.
+    Cancelled,
+    /// The program called `exit` with this code. Can't be caught and isn't reported as an error.
+    Exit(i32),
+}
+
+impl ErrType {
+    /// Whether the program was stopped by one of its limits, see `execute::Limits`.
+    pub fn is_abort(&self) -> bool {
+        matches!(self, ErrType::StepLimit | ErrType::Timeout | ErrType::Cancelled)
+    }
+
+    /// Whether `try` statements may catch the error. Aborts and exits stop the whole program.
+    pub fn is_catchable(&self) -> bool {
+        !self.is_abort() && !matches!(self, ErrType::Exit(_))
+    }
+}
+
+/// How errors are reported.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Code with the error underlined, see `LoxError::generate_err_msg`.
+    #[default]
+    Human,
+    /// One JSON object per error, see `errors::json`.
+    Json,
+}
+
+/// Call of a Lox function which was in progress when an error occured.
+#[derive(Clone, Debug)]
+pub struct CallFrame {
+    pub function: String,
+    pub call_site: Span,
+}
+
+impl CallFrame {
+    fn same_call(&self, other: &CallFrame) -> bool {
+        self.function == other.function && self.call_site == other.call_site
+    }
 }
 
 #[derive(Clone, Debug)]
 pub struct LoxError {
     pub msg: String,
+    /// What the interpreter was doing when the error occured, if known.
+    pub while_info: Option<Box<str>>,
     pub err_type: ErrType,
-    pub pos: Position,
+    pub span: Span,
+    /// Calls leading to the error, outermost first. Empty for errors outside of functions.
+    pub stack: Vec<CallFrame>,
+    /// Value of an `Exception`, as passed to `throw`.
+    pub thrown: Option<Box<LoxObj>>,
 }
 
 impl LoxError {
-    /// Generates a 3-line error message shown directly in the CLI. Looks smth like this:
+    /// Where the error starts.
+    pub fn pos(&self) -> Position {
+        self.span.start
+    }
+
+    /// The message, followed by what the interpreter was doing when the error occured.
+    pub fn full_msg(&self) -> String {
+        match &self.while_info {
+            Some(info) => format!("{}\nError occured while: {}", self.msg, info),
+            None => self.msg.clone(),
+        }
+    }
+
+    /// Generates an error message shown directly in the CLI, which underlines the span of the
+    /// error in the source it comes from. Looks smth like this:
+    /// --> file.lox:12:5
     /// |
     /// | [line number] code
-    /// |                 ^ error message
+    /// |               ^^^^ error message
+    ///
+    /// Spans over several lines underline each of them, with the message after the last one.
+    pub fn generate_err_msg(&self, sources: &SourceMap) -> String {
+        let lines: Vec<&str> = match sources.get(self.span.file_id) {
+            Some(file) => file.source.lines().collect(),
+            None => Vec::new(),
+        };
+        let (first_line, end_line) = (self.span.start.line as usize, self.span.end.line as usize);
+        if self.span.is_none() || first_line >= lines.len() {
+            // errors raised by the host, or in a source which was dropped since
+            let msg_line = format!(" | {}\n", self.full_msg());
+            return [" |\n".to_string(), msg_line, self.generate_traceback(sources)].join("");
+        }
+
+        let last_line = end_line.clamp(first_line, lines.len() - 1);
+        let mut msg = match sources.location(self.span) {
+            Some(location) => format!(" --> {}\n |\n", location),
+            None => " |\n".to_string(),
+        };
+        let lines = lines.iter().enumerate().skip(first_line).take(last_line + 1 - first_line);
+        for (n, line) in lines {
+            let indent = line.chars().take_while(|c| c.is_whitespace()).count();
+            let from = if n == first_line { self.span.start.line_pos as usize } else { indent };
+            let to = if n == end_line { self.span.end.line_pos as usize } else { line.chars().count() };
+
+            let code_line_base = format!(" | [{}] ", n + 1);
+            let pointer = " ".repeat(from + code_line_base.len() - " | ".len())
+                + &"^".repeat(to.saturating_sub(from).max(1));
+            msg += &(code_line_base + line + "\n");
+            msg += &(" | ".to_string() + &pointer);
+            if n == last_line {
+                msg += &(" ".to_string() + &self.full_msg());
+            }
+            msg += "\n";
+        }
+
+        msg + &self.generate_traceback(sources)
+    }
+
+    /// Lists the calls which led to the error, most recent first:
+    /// | Traceback (most recent call first):
+    /// |   in fib, called at fib.lox:12:5
     ///
-    pub fn generate_err_msg(&self, program: &String) -> String {
-        let line = program.lines().nth(self.pos.line).unwrap_or_else(|| {
-            panic!(
-                "Failed to generate error message: line {} is out of range. Error details: {:?}",
-                self.pos.line, self
-            )
-        });
-
-        let top_line = " |\n".to_string();
-        let middle_line_base = format!(" | [{}] ", self.pos.line + 1);
-        let bottom_line_pointer = " "
-            .to_string()
-            .repeat(self.pos.line_pos + middle_line_base.len() - " | ".len())
-            + "^";
-        let middle_line = middle_line_base + line + "\n";
-        let bottom_line = " | ".to_string() + &bottom_line_pointer + " " + &self.msg + "\n";
-
-        return [top_line, middle_line, bottom_line].join("");
+    pub fn generate_traceback(&self, sources: &SourceMap) -> String {
+        if self.stack.is_empty() {
+            return "".to_string();
+        }
+
+        let mut traceback = " | Traceback (most recent call first):\n".to_string();
+        let mut frames = self.stack.iter().rev().peekable();
+        while let Some(frame) = frames.next() {
+            let call_site = match sources.location(frame.call_site) {
+                Some(location) => format!("called at {}", location),
+                None if frame.call_site.is_none() => "called by the host".to_string(),
+                None => format!("called at line {}", frame.call_site.start.line + 1),
+            };
+            traceback += &format!(" |   in {}, {}\n", frame.function, call_site);
+
+            // deep recursion would bury the rest of the trace
+            let mut repeated = 0;
+            while frames.next_if(|next| next.same_call(frame)).is_some() {
+                repeated += 1;
+            }
+            if repeated > 0 {
+                traceback += &format!(" |   ... repeated {} more times\n", repeated);
+            }
+        }
+        traceback
     }
 }
 
+/// Part of an error which was not supplied to the `ErrBuilder` yet.
+#[derive(Clone, Copy, Debug)]
+pub struct Missing;
+
+/// Builds a `LoxError` step by step. The type parameters record whether the type, span and
+/// message were supplied, so `build` only exists once all of them are.
 #[derive(Clone, Debug)]
-pub struct ErrBuilder {
-    err_type: Option<ErrType>,
-    message: Option<String>,
+pub struct ErrBuilder<T = Missing, P = Missing, M = Missing> {
+    err_type: T,
+    span: P,
+    message: M,
     while_info: Option<String>,
-    pos: Option<Position>,
 }
 
 impl ErrBuilder {
     pub fn new() -> Self {
         ErrBuilder {
-            err_type: None,
+            err_type: Missing,
+            span: Missing,
+            message: Missing,
             while_info: None,
-            message: None,
-            pos: None,
         }
     }
+}
 
-    pub fn of_type(mut self, err_type: ErrType) -> Self {
-        self.err_type = Some(err_type);
-        self
+impl<T, P, M> ErrBuilder<T, P, M> {
+    pub fn of_type(self, err_type: ErrType) -> ErrBuilder<ErrType, P, M> {
+        ErrBuilder {
+            err_type,
+            span: self.span,
+            message: self.message,
+            while_info: self.while_info,
+        }
     }
 
-    pub fn with_message(mut self, message: String) -> Self {
-        self.message = Some(message);
-        self
+    pub fn with_message(self, message: String) -> ErrBuilder<T, P, String> {
+        ErrBuilder {
+            err_type: self.err_type,
+            span: self.span,
+            message,
+            while_info: self.while_info,
+        }
     }
 
-    pub fn with_pos(mut self, pos: Position) -> Self {
-        self.pos = Some(pos.clone());
-        self
+    pub fn with_pos(self, pos: impl Into<Span>) -> ErrBuilder<T, Span, M> {
+        self.at(pos)
     }
 
-    pub fn without_pos(mut self) -> Self {
-        self.pos = Some(Position {
-            line: 0,
-            line_pos: 0,
-        }); // TODO: make it suck less
-        self
+    /// For errors which aren't tied to any place in the program, see `Span::none`.
+    pub fn without_pos(self) -> ErrBuilder<T, Span, M> {
+        self.at(Span::none())
     }
 
-    pub fn at(mut self, pos: Position) -> Self {
-        self.pos = Some(pos);
-        self
+    /// Either a `Position`, or the `Span` of the code the error is about.
+    pub fn at(self, pos: impl Into<Span>) -> ErrBuilder<T, Span, M> {
+        ErrBuilder {
+            err_type: self.err_type,
+            span: pos.into(),
+            message: self.message,
+            while_info: self.while_info,
+        }
     }
 
-    pub fn expected_but_found<E, F>(mut self, expected: E, found: F) -> Self
+    pub fn expected_but_found<E, F>(self, expected: E, found: F) -> ErrBuilder<T, P, String>
     where
         E: std::fmt::Debug,
         F: std::fmt::Debug,
     {
-        self.message = Some(format!("Expected {:?}, but found {:?}.", expected, found));
-        self
+        self.with_message(format!("Expected {:?}, but found {:?}.", expected, found))
     }
 
-    pub fn is_not<A, B>(mut self, a: A, b: B) -> Self
+    pub fn is_not<A, B>(self, a: A, b: B) -> ErrBuilder<T, P, String>
     where
         A: std::fmt::Debug,
         B: std::fmt::Debug,
     {
-        self.message = Some(format!("{:?} is not {:?}.", a, b));
-        self
+        self.with_message(format!("{:?} is not {:?}.", a, b))
     }
 
     pub fn while_<A>(mut self, msg: A) -> Self
     where
-        A: std::fmt::Debug,
+        A: std::fmt::Display,
     {
-        self.while_info = Some(format!("{:?}", msg));
+        self.while_info = Some(msg.to_string());
         self
     }
 
-    pub fn expected_found_nothing<E>(mut self, expected: E) -> Self
+    pub fn expected_found_nothing<E>(self, expected: E) -> ErrBuilder<T, P, String>
     where
         E: std::fmt::Debug,
     {
-        self.message = Some(format!("Expected {:?}, but found nothing.", expected));
-        self
+        self.with_message(format!("Expected {:?}, but found nothing.", expected))
     }
 
     pub fn cant_perform_a_on_b_and_c<A: std::fmt::Debug, B: std::fmt::Debug, C: std::fmt::Debug>(
@@ -136,46 +271,28 @@ impl ErrBuilder {
         a: A,
         b: B,
         c: C,
-    ) -> ErrBuilder {
+    ) -> ErrBuilder<T, P, String> {
         self.with_message(format!("Can't perform {:?} on {:?} and {:?}", a, b, c))
     }
 
-    pub fn reset(mut self) -> Self {
-        self.err_type = None;
-        self.message = None;
-        self.while_info = None;
-        self.pos = None;
-        self
+    pub fn reset(self) -> ErrBuilder {
+        ErrBuilder::new()
     }
+}
 
+impl ErrBuilder<ErrType, Span, String> {
     pub fn to_result<A>(self) -> LoxResult<A> {
         Err(self.build())
     }
 
     pub fn build(self) -> LoxError {
-        let self_repr = format!("{:?}", &self);
-        let msg_core = &self.message.unwrap_or_else(|| {
-            panic!(
-                "ErrBuilder failed, message was not supplied: {:?}",
-                self_repr
-            )
-        });
-
-        let while_info = &self.while_info.map_or("".to_string(), |info| {
-            "\nError occured while: ".to_string() + &info
-        });
-
         LoxError {
-            msg: msg_core.clone() + &while_info,
-            err_type: self.err_type.unwrap_or_else(|| {
-                panic!(
-                    "ErrBuilder failed: err_type was not supplied: {:?}",
-                    self_repr
-                )
-            }),
-            pos: self.pos.unwrap_or_else(|| {
-                panic!("ErrBuilder failed: pos was not supplied: {:?}", self_repr)
-            }),
+            msg: self.message,
+            while_info: self.while_info.map(String::into_boxed_str),
+            err_type: self.err_type,
+            span: self.span,
+            stack: Vec::new(),
+            thrown: None,
         }
     }
 }
@@ -0,0 +1,115 @@
+//! Machine readable form of errors, used by `--error-format=json` for editors and CI.
+//!
+//! Each error is written as one JSON object on a line of its own, e.g.
+//!
+//! ```text
+//! {"version":1,"severity":"error","type":"RuntimeError","message":"Variable x is not in scope",
+//!  "while":null,"file":"main.lox","span":{"start":{"line":3,"column":7,"offset":25},
+//!  "end":{"line":3,"column":8,"offset":26}},"stack":[{"function":"f","file":"main.lox",
+//!  "span":{"start":{"line":5,"column":1,"offset":40},"end":{"line":5,"column":4,"offset":43}}}]}
+//! ```
+//!
+//! * `version`: version of this format, raised whenever a field changes or goes away. New fields
+//!   may be added without raising it.
+//! * `severity`: always `"error"` for now.
+//! * `type`: kind of the error, one of the `ErrType` variants, e.g. `"ParsingErr"`.
+//! * `message`: what went wrong.
+//! * `while`: what the interpreter was doing when the error occured, or `null`.
+//! * `file`: name of the source the error is in, or `null` for errors which aren't tied to any
+//!   source, like those raised by the host. `span` is `null` then as well.
+//! * `span`: part of the source the error is about. Lines and columns are counted from 1 and
+//!   columns in characters, `offset` is counted in bytes from 0. `end` is exclusive.
+//! * `stack`: calls in progress when the error occured, most recent first, with the `file` and
+//!   `span` of each call site. Empty for errors outside of functions.
+//!
+//! The CLI writes the errors to stderr, so they don't mix with what the program prints.
+
+use crate::interpreter::errors::position::Position;
+use crate::interpreter::errors::source_map::SourceMap;
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::LoxError;
+
+/// Version of the JSON format, see the module documentation.
+pub const JSON_FORMAT_VERSION: u32 = 1;
+
+impl LoxError {
+    /// The error as a single line JSON object, in the format described in `errors::json`.
+    pub fn to_json(&self, sources: &SourceMap) -> String {
+        let stack: Vec<String> = self
+            .stack
+            .iter()
+            .rev()
+            .map(|frame| {
+                object(&[
+                    ("function", string(&frame.function)),
+                    ("file", file(sources, frame.call_site)),
+                    ("span", span(sources, frame.call_site)),
+                ])
+            })
+            .collect();
+
+        object(&[
+            ("version", JSON_FORMAT_VERSION.to_string()),
+            ("severity", string("error")),
+            ("type", string(&format!("{:?}", self.err_type))),
+            ("message", string(&self.msg)),
+            ("while", self.while_info.as_deref().map_or(NULL.to_string(), string)),
+            ("file", file(sources, self.span)),
+            ("span", span(sources, self.span)),
+            ("stack", format!("[{}]", stack.join(","))),
+        ])
+    }
+}
+
+const NULL: &str = "null";
+
+/// `fields` have to be serialized already.
+fn object(fields: &[(&str, String)]) -> String {
+    let fields: Vec<String> = fields
+        .iter()
+        .map(|(key, value)| format!("{}:{}", string(key), value))
+        .collect();
+    format!("{{{}}}", fields.join(","))
+}
+
+fn string(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len() + 2);
+    escaped.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            '\t' => escaped.push_str("\\t"),
+            c if (c as u32) < 0x20 => escaped.push_str(&format!("\\u{:04x}", c as u32)),
+            c => escaped.push(c),
+        }
+    }
+    escaped.push('"');
+    escaped
+}
+
+fn file(sources: &SourceMap, span: Span) -> String {
+    if span.is_none() {
+        return NULL.to_string();
+    }
+    sources
+        .get(span.file_id)
+        .map_or(NULL.to_string(), |file| string(&file.name))
+}
+
+fn span(sources: &SourceMap, span: Span) -> String {
+    match sources.get(span.file_id) {
+        Some(_) if !span.is_none() => object(&[("start", position(span.start)), ("end", position(span.end))]),
+        _ => NULL.to_string(),
+    }
+}
+
+fn position(pos: Position) -> String {
+    object(&[
+        ("line", (pos.line + 1).to_string()),
+        ("column", (pos.line_pos + 1).to_string()),
+        ("offset", pos.offset.to_string()),
+    ])
+}
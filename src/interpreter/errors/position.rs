@@ -1,14 +1,17 @@
-#[derive(Clone, Copy, Eq, PartialEq, Debug)]
+/// Place in the source, as a line and column (both counted from 0) and as a byte offset.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
 pub struct Position {
-    pub line: usize,
-    pub line_pos: usize,
+    pub line: u32,
+    pub line_pos: u32,
+    pub offset: u32,
 }
 
-impl Into<Position> for (usize, usize) {
+impl Into<Position> for (u32, u32) {
     fn into(self) -> Position {
         Position {
             line: self.0,
             line_pos: self.1,
+            offset: 0,
         }
     }
 }
@@ -0,0 +1,64 @@
+use crate::interpreter::errors::span::{FileId, Span};
+use std::collections::HashMap;
+
+/// Source loaded into the interpreter, e.g. a file, a line typed into the REPL or an `eval` string.
+pub struct SourceFile {
+    pub name: String,
+    pub source: String,
+}
+
+/// Owns all sources loaded into the interpreter, so errors can be shown next to the code they
+/// are about, wherever it came from. Ids start at 1, spans with the default id 0 come from code
+/// which was never added, and `Span::none` from no code at all. Ids of removed sources are not
+/// reused.
+#[derive(Default)]
+pub struct SourceMap {
+    files: HashMap<FileId, SourceFile>,
+    last_id: FileId,
+}
+
+impl SourceMap {
+    pub fn new() -> Self {
+        SourceMap::default()
+    }
+
+    /// Takes over `source`, and returns the id to tag its spans with.
+    pub fn add(&mut self, name: impl Into<String>, source: impl Into<String>) -> FileId {
+        self.last_id += 1;
+        let file = SourceFile {
+            name: name.into(),
+            source: source.into(),
+        };
+        self.files.insert(self.last_id, file);
+        self.last_id
+    }
+
+    /// Drops the source, errors pointing into it are shown without the code afterwards.
+    pub fn remove(&mut self, file_id: FileId) -> Option<SourceFile> {
+        self.files.remove(&file_id)
+    }
+
+    pub fn get(&self, file_id: FileId) -> Option<&SourceFile> {
+        self.files.get(&file_id)
+    }
+
+    /// Number of sources currently held.
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Where `span` starts as `name:line:column`, counted from 1 like in editors.
+    pub fn location(&self, span: Span) -> Option<String> {
+        let file = self.get(span.file_id)?;
+        Some(format!(
+            "{}:{}:{}",
+            file.name,
+            span.start.line + 1,
+            span.start.line_pos + 1
+        ))
+    }
+}
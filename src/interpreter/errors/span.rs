@@ -0,0 +1,66 @@
+use crate::interpreter::errors::position::Position;
+
+/// Identifies the source a span belongs to.
+pub type FileId = usize;
+
+/// File id of `Span::none`, which no source in a `SourceMap` ever gets.
+const NO_SOURCE: FileId = FileId::MAX;
+
+/// Part of a source, from `start` up to but excluding `end`.
+#[derive(Clone, Copy, Eq, PartialEq, Debug, Default)]
+pub struct Span {
+    pub start: Position,
+    pub end: Position,
+    pub file_id: FileId,
+}
+
+impl Span {
+    pub fn new(start: Position, end: Position) -> Self {
+        Span {
+            start,
+            end,
+            file_id: 0,
+        }
+    }
+
+    /// Span of errors which aren't tied to any place in a source, e.g. those raised by the host
+    /// or failing to read a file.
+    pub fn none() -> Self {
+        Span {
+            file_id: NO_SOURCE,
+            ..Span::default()
+        }
+    }
+
+    /// Whether this is `Span::none`, whose positions don't mean anything.
+    pub fn is_none(&self) -> bool {
+        self.file_id == NO_SOURCE
+    }
+
+    /// Span from the start of `self` to the end of `other`.
+    pub fn to(self, other: Span) -> Span {
+        Span {
+            end: other.end,
+            ..self
+        }
+    }
+
+    /// Empty span right after `self`, in the same source.
+    pub fn at_end(self) -> Span {
+        Span {
+            start: self.end,
+            ..self
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+}
+
+/// Errors which only know where they happen point at an empty span there.
+impl From<Position> for Span {
+    fn from(pos: Position) -> Self {
+        Span::new(pos, pos)
+    }
+}
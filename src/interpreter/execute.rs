@@ -1,9 +1,23 @@
 //! Contains all the runtime logic needed for executing the AST.
 
+pub use capabilities::{Capabilities, Capability};
+pub use definitions::{LoxObj, OrderedMap};
 pub use executing::Executor;
+pub use heap::HeapStats;
+pub use host::HostObject;
+pub use limits::Limits;
+pub use native::{Ctx, NativeFunction};
 
+mod capabilities;
+mod classes;
 mod definitions;
+mod exceptions;
 mod executing;
+mod generators;
+mod heap;
+mod host;
 mod inbuilt;
+mod limits;
+mod native;
 mod operations;
 mod state;
@@ -0,0 +1,146 @@
+//! Capabilities a script needs to touch anything outside of the interpreter.
+//!
+//! Built-in functions with side effects declare the capability they need with
+//! `NativeFunction::requires` when `inbuilt::register_all` registers them, host functions with
+//! `LoxInterpreter::register_native_with_capability`. Calling one which was not granted is a
+//! runtime error, so untrusted scripts can be run with nothing granted.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxResult};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Capability {
+    FsRead,
+    FsWrite,
+    Env,
+    Clock,
+    Exit,
+    Stdin,
+}
+
+impl fmt::Display for Capability {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            Capability::FsRead => "fs.read",
+            Capability::FsWrite => "fs.write",
+            Capability::Env => "env",
+            Capability::Clock => "clock",
+            Capability::Exit => "exit",
+            Capability::Stdin => "stdin",
+        };
+        write!(f, "{}", name)
+    }
+}
+
+/// Set of granted capabilities. Nothing is granted by default.
+#[derive(Debug, Clone, Default)]
+pub struct Capabilities {
+    granted: HashSet<Capability>,
+    /// Directories which filesystem capabilities are restricted to. Capabilities without an entry
+    /// apply to any path.
+    roots: HashMap<Capability, Vec<PathBuf>>,
+}
+
+impl Capabilities {
+    pub fn none() -> Self {
+        Self::default()
+    }
+
+    pub fn all() -> Self {
+        let mut capabilities = Self::none();
+        for capability in [
+            Capability::FsRead,
+            Capability::FsWrite,
+            Capability::Env,
+            Capability::Clock,
+            Capability::Exit,
+            Capability::Stdin,
+        ] {
+            capabilities = capabilities.allow(capability);
+        }
+        capabilities
+    }
+
+    pub fn allow(mut self, capability: Capability) -> Self {
+        self.granted.insert(capability);
+        self.roots.remove(&capability);
+        self
+    }
+
+    /// Allows reading files inside of `dir`.
+    pub fn allow_read(self, dir: impl AsRef<Path>) -> Self {
+        self.allow_in(Capability::FsRead, dir.as_ref())
+    }
+
+    /// Allows writing files inside of `dir`.
+    pub fn allow_write(self, dir: impl AsRef<Path>) -> Self {
+        self.allow_in(Capability::FsWrite, dir.as_ref())
+    }
+
+    fn allow_in(mut self, capability: Capability, dir: &Path) -> Self {
+        // once granted for any path, a directory can't restrict it
+        if self.granted.contains(&capability) && !self.roots.contains_key(&capability) {
+            return self;
+        }
+        self.granted.insert(capability);
+        let dir = dir.canonicalize().unwrap_or_else(|_| dir.to_path_buf());
+        self.roots.entry(capability).or_default().push(dir);
+        self
+    }
+
+    pub fn check(&self, capability: Capability, pos: Span) -> LoxResult<()> {
+        match self.granted.contains(&capability) {
+            true => Ok(()),
+            false => denied(capability, pos).to_result(),
+        }
+    }
+
+    /// Checks `capability` for a file at `path`, which has to be inside of one of the allowed
+    /// directories if there are any.
+    pub fn check_path(&self, capability: Capability, path: &Path, pos: Span) -> LoxResult<()> {
+        self.check(capability, pos)?;
+        let roots = match self.roots.get(&capability) {
+            Some(roots) => roots,
+            None => return Ok(()),
+        };
+
+        let inside = resolve(path).is_some_and(|path| roots.iter().any(|root| path.starts_with(root)));
+        match inside {
+            true => Ok(()),
+            false => ErrBuilder::new()
+                .of_type(ErrType::RuntimeError)
+                .at(pos)
+                .with_message(format!(
+                    "permission denied: {} of {} outside of the allowed directories",
+                    capability,
+                    path.display()
+                ))
+                .to_result(),
+        }
+    }
+}
+
+fn denied(capability: Capability, pos: Span) -> ErrBuilder<ErrType, Span, String> {
+    ErrBuilder::new()
+        .of_type(ErrType::RuntimeError)
+        .at(pos)
+        .with_message(format!("permission denied: {}", capability))
+}
+
+/// Absolute path without `..` or symlinks. Files which don't exist yet are resolved through their
+/// parent directory.
+fn resolve(path: &Path) -> Option<PathBuf> {
+    if let Ok(path) = path.canonicalize() {
+        return Some(path);
+    }
+    let parent = match path.parent() {
+        Some(parent) if parent.as_os_str().is_empty() => Path::new("."),
+        Some(parent) => parent,
+        None => return None,
+    };
+    Some(parent.canonicalize().ok()?.join(path.file_name()?))
+}
@@ -0,0 +1,106 @@
+//! Classes: constructing instances, reading their fields and calling their methods.
+//!
+//! Host objects are accessed through the same `.field` and `.method()` syntax, so they are
+//! dispatched here as well.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::LoxResult;
+use crate::interpreter::execute::definitions::{Instance, LoxObj};
+use crate::interpreter::execute::executing::Executor;
+use crate::interpreter::execute::heap::allocated_size;
+use crate::interpreter::execute::native::Ctx;
+use crate::interpreter::execute::operations::eval_err;
+use crate::interpreter::parser::structure::ClassDefinition;
+use crate::interpreter::parser::visitor::Visitor;
+use crate::interpreter::tokens::{LoxValue, Token};
+
+impl Executor {
+    /// Creates an instance with fields set to their default values. Arguments override the fields
+    /// in the order in which they were declared.
+    pub(super) fn call_constructor(&mut self, class_def: &ClassDefinition, args: Vec<LoxObj>, pos: Span) -> LoxResult<LoxObj> {
+        if args.len() > class_def.fields.len() {
+            return eval_err()
+                .at(pos)
+                .with_message(format!(
+                    "{} has {} fields, but got {} arguments",
+                    class_def.name,
+                    class_def.fields.len(),
+                    args.len()
+                ))
+                .to_result();
+        }
+
+        let instance = Instance {
+            class: class_def.clone(),
+            fields: HashMap::new(),
+        };
+        let obj = LoxObj::Object(Rc::new(RefCell::new(instance)));
+
+        let mut args = args.into_iter();
+        for (lval, rval) in class_def.fields.iter() {
+            let value = match args.next() {
+                Some(arg) => arg,
+                None => self.visit(&rval.expr)?,
+            };
+            obj.set(lval.identifier.clone(), value)?;
+        }
+
+        self.allocate(allocated_size(&obj), pos)?;
+        Ok(obj)
+    }
+
+    pub(super) fn get_property(&mut self, target: &LoxObj, name: &Token) -> LoxResult<LoxObj> {
+        let field_name = name.as_identifier()?;
+        let field = match target {
+            LoxObj::Object(instance) => instance.borrow().fields.get(field_name).cloned(),
+            LoxObj::Host(host) => {
+                let mut ctx = Ctx { executor: self, pos: name.span };
+                return host.get_property(&mut ctx, field_name);
+            }
+            _ => None,
+        };
+
+        field.ok_or(
+            eval_err()
+                .at(name.span)
+                .with_message(format!("{} has no field {}", target.to_string(), field_name))
+                .build(),
+        )
+    }
+
+    /// Calls `target.method_name(args)`. Methods of instances run with `this` bound to the instance.
+    pub(super) fn call_method(&mut self, target: &LoxObj, method_name: &str, args: Vec<LoxObj>, pos: Span) -> LoxResult<LoxObj> {
+        let no_such_method = || {
+            eval_err()
+                .at(pos)
+                .with_message(format!("{} has no method {}", target.to_string(), method_name))
+                .to_result()
+        };
+
+        match target {
+            LoxObj::Object(instance) => {
+                let field = instance.borrow().fields.get(method_name).cloned();
+                if let Some(func) = field {
+                    return self.call(&func, args, pos);
+                }
+
+                let method = match instance.borrow().method(method_name) {
+                    Some(method) => method.clone(),
+                    None => return no_such_method(),
+                };
+                self.call_function(&method, Some(target.clone()), args, pos)
+            }
+            LoxObj::Host(host) => host.call_method(&mut Ctx { executor: self, pos }, method_name, args),
+            LoxObj::Iterator(_) | LoxObj::Generator(_) => match method_name {
+                "next" => Ok(self.next_item(target, pos)?.unwrap_or(LoxObj::Plain(LoxValue::Nil))),
+                "iter" => Ok(target.clone()),
+                _ => no_such_method(),
+            },
+            _ => no_such_method(),
+        }
+    }
+}
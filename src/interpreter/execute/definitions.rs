@@ -1,4 +1,4 @@
-use crate::interpreter::tokens::TokenValue::Id;
+use crate::interpreter::errors::{ErrBuilder, ErrType::RuntimeError, LoxResult};
 use crate::interpreter::tokens::Token;
 use std::cell::RefCell;
 use std::rc::Rc;
@@ -6,40 +6,254 @@ use std::collections::HashMap;
 use crate::interpreter::parser::structure::ClassDefinition;
 use crate::interpreter::parser::structure::FunctionDefinition;
 use crate::interpreter::tokens::LoxValue;
+use crate::interpreter::execute::generators::Generator;
+use crate::interpreter::execute::host::HostObject;
+use crate::interpreter::execute::native::NativeFunction;
 
 /// Value which is held in the bindings
 #[derive(Debug, Clone)]
 pub enum LoxObj {
-    Object(HashMap<String, LoxObj>),
+    Object(LoxInstance),
     Plain(LoxValue),
     Fun(FunctionDefinition),
-    Inbuilt(String),
+    Native(Rc<NativeFunction>),
+    Host(Rc<dyn HostObject>),
     Class(ClassDefinition),
+    List(LoxList),
+    Map(LoxMap),
+    Iterator(LoxIterator),
+    Generator(LoxGenerator),
 }
 
 pub type LoxObjRef = Rc<RefCell<LoxObj>>;
 
+/// Lists are shared - copying a list value copies the reference, not the elements.
+pub type LoxList = Rc<RefCell<Vec<LoxObj>>>;
+
+/// Instances of classes are shared, so methods can modify their fields.
+pub type LoxInstance = Rc<RefCell<Instance>>;
+
+#[derive(Debug, Clone)]
+pub struct Instance {
+    pub class: ClassDefinition,
+    pub fields: HashMap<String, LoxObj>,
+}
+
+impl Instance {
+    pub fn method(&self, name: &str) -> Option<&FunctionDefinition> {
+        self.class
+            .methods
+            .iter()
+            .find(|method| method.name.identifier().map(|n| n.as_str()) == Some(name))
+    }
+}
+
+/// Iterators are shared as well - advancing a copy advances the original.
+pub type LoxIterator = Rc<RefCell<NativeIterator>>;
+
+/// State of iteration over a built-in collection or a range.
+#[derive(Debug, Clone)]
+pub enum NativeIterator {
+    /// Goes over the live list, so items pushed while iterating are visited too.
+    List { items: LoxList, next: usize },
+    /// Goes over a snapshot, e.g. of map keys or string characters.
+    Snapshot { items: Vec<LoxObj>, next: usize },
+    Range { next: i32, end: i32, step: i32 },
+}
+
+impl NativeIterator {
+    pub fn next(&mut self) -> Option<LoxObj> {
+        match self {
+            NativeIterator::List { items, next } => {
+                let item = items.borrow().get(*next).cloned();
+                *next += 1;
+                item
+            }
+            NativeIterator::Snapshot { items, next } => {
+                let item = items.get(*next).cloned();
+                *next += 1;
+                item
+            }
+            NativeIterator::Range { next, end, step } => {
+                let has_next = (*step > 0 && *next < *end) || (*step < 0 && *next > *end);
+                if !has_next {
+                    return None;
+                }
+                let item = LoxObj::Plain(LoxValue::Integer(*next as i16));
+                *next += *step;
+                Some(item)
+            }
+        }
+    }
+}
+
+pub type LoxGenerator = Rc<RefCell<Generator>>;
+
+/// Maps are shared in the same way as lists.
+pub type LoxMap = Rc<RefCell<OrderedMap>>;
+
+/// Hash map which remembers the order in which keys were first inserted.
+#[derive(Debug, Clone, Default)]
+pub struct OrderedMap {
+    /// Removed entries leave a `None` behind, so the positions of later keys stay valid until
+    /// the entries get compacted.
+    entries: Vec<Option<(LoxValue, LoxObj)>>,
+    positions: HashMap<LoxValue, usize>,
+}
+
+impl OrderedMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    pub fn get(&self, key: &LoxValue) -> Option<&LoxObj> {
+        let i = self.positions.get(key)?;
+        self.entries.get(*i)?.as_ref().map(|(_, value)| value)
+    }
+
+    pub fn contains_key(&self, key: &LoxValue) -> bool {
+        self.positions.contains_key(key)
+    }
+
+    /// Overwriting a value keeps the original position of its key.
+    pub fn insert(&mut self, key: LoxValue, value: LoxObj) {
+        let entry = self.positions.get(&key).and_then(|i| self.entries.get_mut(*i));
+        match entry {
+            Some(Some(entry)) => entry.1 = value,
+            _ => {
+                self.positions.insert(key.clone(), self.entries.len());
+                self.entries.push(Some((key, value)));
+            }
+        }
+    }
+
+    pub fn remove(&mut self, key: &LoxValue) -> Option<LoxObj> {
+        let i = self.positions.remove(key)?;
+        let (_, removed) = self.entries.get_mut(i)?.take()?;
+        // compacting once most entries are removed keeps removal amortized O(1)
+        if self.entries.len() > 2 * self.positions.len() + 8 {
+            self.compact();
+        }
+        Some(removed)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &(LoxValue, LoxObj)> {
+        self.entries.iter().flatten()
+    }
+
+    fn compact(&mut self) {
+        self.entries.retain(Option::is_some);
+        for (i, (key, _)) in self.entries.iter().flatten().enumerate() {
+            self.positions.insert(key.clone(), i);
+        }
+    }
+}
+
 impl LoxObj {
-    pub fn set(&mut self, id: Token, obj: LoxObj) {
-        let name = match id.val {
-            Id(name) => name,
-            _ => panic!("Attempted to assign value to non-identifier.")
-        };
-        let mut map = match self {
-            LoxObj::Object(map) => map,
-            _ => panic!("cannot assign to this object")
+    pub fn set(&self, id: Token, obj: LoxObj) -> LoxResult<()> {
+        let name = id.as_identifier()?;
+        let instance = match self {
+            LoxObj::Object(instance) => instance,
+            _ => {
+                return ErrBuilder::new()
+                    .of_type(RuntimeError)
+                    .at(id.span)
+                    .with_message(format!("Cannot set properties on {}", self.to_string()))
+                    .to_result()
+            }
         };
 
-        map.insert(name, obj);
+        instance.borrow_mut().fields.insert(name.clone(), obj);
+        Ok(())
     }
 
     pub fn to_string(&self) -> String {
-        match self {
+        self.to_string_inner(&mut Vec::new())
+    }
+
+    /// `path` holds the containers being printed, like in `Value::from_lox`, so a list containing
+    /// itself is printed as `[...]` the second time instead of forever.
+    fn to_string_inner(&self, path: &mut Vec<*const ()>) -> String {
+        let ptr = match self {
+            LoxObj::List(items) => Some(Rc::as_ptr(items) as *const ()),
+            LoxObj::Map(map) => Some(Rc::as_ptr(map) as *const ()),
+            _ => None,
+        };
+        if let Some(ptr) = ptr {
+            if path.contains(&ptr) {
+                return match self {
+                    LoxObj::Map(_) => "{...}".to_string(),
+                    _ => "[...]".to_string(),
+                };
+            }
+            path.push(ptr);
+        }
+
+        let string = match self {
             LoxObj::Plain(val) => format!("{:?}", val),
-            LoxObj::Fun(function_def) => format!("Function {:?}", function_def.name),
-            LoxObj::Class(defn) => format!("{:?}", defn.name),
-            LoxObj::Object(_) => format!("Instance"),
-            LoxObj::Inbuilt(name) => format!("Inbuilt Function {}", name)
+            LoxObj::Fun(function_def) => format!("Function {}", function_def.name),
+            LoxObj::Class(defn) => defn.name.to_string(),
+            LoxObj::Object(instance) => format!("{} instance", instance.borrow().class.name),
+            LoxObj::Iterator(_) => format!("Iterator"),
+            LoxObj::Generator(generator) => format!("Generator {}", generator.borrow().name()),
+            LoxObj::Native(function) => format!("Native Function {}", function.name()),
+            LoxObj::Host(host) => host.display(),
+            LoxObj::List(items) => {
+                let items: Vec<String> =
+                    items.borrow().iter().map(|item| item.to_string_inner(path)).collect();
+                format!("[{}]", items.join(", "))
+            }
+            LoxObj::Map(map) => {
+                let entries: Vec<String> = map
+                    .borrow()
+                    .iter()
+                    .map(|(key, value)| format!("{:?}: {}", key, value.to_string_inner(path)))
+                    .collect();
+                format!("{{{}}}", entries.join(", "))
+            }
+        };
+        if ptr.is_some() {
+            path.pop();
+        }
+        string
+    }
+
+    pub fn list(items: Vec<LoxObj>) -> Self {
+        LoxObj::List(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn map(map: OrderedMap) -> Self {
+        LoxObj::Map(Rc::new(RefCell::new(map)))
+    }
+
+    pub fn host(host: impl HostObject + 'static) -> Self {
+        LoxObj::Host(Rc::new(host))
+    }
+
+    pub fn iterator(iterator: NativeIterator) -> Self {
+        LoxObj::Iterator(Rc::new(RefCell::new(iterator)))
+    }
+
+    /// Values are compared by value, everything else by reference.
+    pub fn equals(&self, other: &LoxObj) -> bool {
+        match (self, other) {
+            (LoxObj::Plain(a), LoxObj::Plain(b)) => a == b,
+            (LoxObj::List(a), LoxObj::List(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Map(a), LoxObj::Map(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Object(a), LoxObj::Object(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Iterator(a), LoxObj::Iterator(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Generator(a), LoxObj::Generator(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Native(a), LoxObj::Native(b)) => Rc::ptr_eq(a, b),
+            (LoxObj::Host(a), LoxObj::Host(b)) => Rc::ptr_eq(a, b),
+            _ => false,
         }
     }
 
@@ -0,0 +1,92 @@
+//! `throw` and `try` statements.
+//!
+//! Exceptions travel up the executor as a `LoxError`, the same way as errors raised by the
+//! interpreter itself, so both can be caught. Values thrown from Lox code ride along in the
+//! error's `thrown` field.
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::rc::Rc;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxError, LoxResult};
+use crate::interpreter::execute::definitions::{Instance, LoxObj};
+use crate::interpreter::execute::executing::{Evaluated, Executor};
+use crate::interpreter::parser::structure::{ClassDefinition, TryStatement};
+use crate::interpreter::parser::visitor::Visitor;
+use crate::interpreter::tokens::{LoxValue, Token, TokenValue};
+
+impl Executor {
+    pub(super) fn throw(&mut self, value: LoxObj, pos: Span) -> LoxResult<Evaluated> {
+        let mut err = ErrBuilder::new()
+            .of_type(ErrType::Exception)
+            .at(pos)
+            .with_message(format!("Uncaught {}", value.to_string()))
+            .build();
+        err.thrown = Some(Box::new(value));
+        Err(err)
+    }
+
+    pub(super) fn try_catch(&mut self, try_stmt: &TryStatement) -> LoxResult<Evaluated> {
+        let mut outcome = self.scoped(|v| v.visit(&try_stmt.body));
+        if is_aborted(&outcome) {
+            return outcome;
+        }
+
+        if let (Err(err), Some((error_var, handler))) = (&outcome, &try_stmt.catch) {
+            let caught = caught_value(err);
+            outcome = self.scoped(|v| {
+                v.state.declare(error_var.clone(), caught)?;
+                v.visit(handler)
+            });
+            if is_aborted(&outcome) {
+                return outcome;
+            }
+        }
+
+        if let Some(cleanup) = &try_stmt.finally {
+            let cleanup_outcome = self.scoped(|v| v.visit(cleanup));
+            let overrides = cleanup_outcome.as_ref().map_or(true, |e| e.is_jump());
+            if overrides {
+                return cleanup_outcome;
+            }
+        }
+
+        outcome
+    }
+}
+
+/// Value bound by `catch`: the thrown value, or an error object for errors raised by the interpreter.
+pub(super) fn caught_value(err: &LoxError) -> LoxObj {
+    match &err.thrown {
+        Some(value) => value.as_ref().clone(),
+        None => error_object(err),
+    }
+}
+
+/// Aborted and exited programs have to stop, so neither `catch` nor `finally` run for them.
+fn is_aborted(outcome: &LoxResult<Evaluated>) -> bool {
+    matches!(outcome, Err(err) if !err.err_type.is_catchable())
+}
+
+/// Instance of the `Error` class with `message`, `type` and `line` fields.
+pub fn error_object(err: &LoxError) -> LoxObj {
+    let id = |name: &str| Token::new(TokenValue::Id(name.to_string()), err.span);
+    let string = |text: String| LoxObj::Plain(LoxValue::String(format!("\"{}\"", text)));
+
+    let class = ClassDefinition {
+        name: id("Error"),
+        fields: Vec::new(),
+        methods: Vec::new(),
+    };
+    let mut fields = HashMap::new();
+    fields.insert("message".to_string(), string(err.msg.clone()));
+    fields.insert("type".to_string(), string(format!("{:?}", err.err_type)));
+    let line = match err.span.is_none() {
+        true => LoxValue::Nil,
+        false => LoxValue::Integer(err.pos().line as i16 + 1),
+    };
+    fields.insert("line".to_string(), LoxObj::Plain(line));
+
+    LoxObj::Object(Rc::new(RefCell::new(Instance { class, fields })))
+}
@@ -1,60 +1,183 @@
 //! A Visitor-style executor for `Vec<Statement>`.
 
-use crate::interpreter::execute::inbuilt::modulo;
-use std::collections::HashMap;
+use crate::interpreter::execute::inbuilt;
 use crate::interpreter::execute::executing::LoxObj::Plain;
-use crate::interpreter::parser::locator::locate;
 use crate::interpreter::tokens::LoxValue;
 use crate::interpreter::{
-    errors::position::Position,
-    errors::LoxResult,
+    errors::span::Span,
+    errors::{CallFrame, ErrBuilder, ErrType, LoxResult},
     execute::{
-        definitions::{LoxObj},
-        operations::{binary_operations, eval_err, unary_op},
+        definitions::LoxObj,
+        operations::{binary_operations, eval_err, get_index, set_index, unary_op},
     },
     parser::structure::*,
     parser::visitor::*,
+    tokens::Kwd,
     tokens::Token,
     tokens::TokenValue,
 };
+use std::cell::RefCell;
 use std::iter::zip;
+use std::rc::Rc;
+use std::io;
+use std::io::Write;
+use std::time::Instant;
 
+use super::capabilities::Capabilities;
+use super::generators::Generator;
+use super::native::{Ctx, Registry};
+use super::heap::{allocated_size, stored_size, Heap, MAP_ENTRY, SLOT};
+use super::limits::Limits;
 use super::state::State;
 
+/// Jump out of a loop body caused by `break` or `continue`, optionally targeting a labeled loop.
+#[derive(Debug, Clone)]
+pub enum Interrupt {
+    Break(Option<String>),
+    Continue(Option<String>),
+}
+
+impl Interrupt {
+    /// Whether the loop labeled `label` is the one which should handle this interrupt.
+    pub fn targets(&self, label: &Option<Token>) -> bool {
+        let target = match self {
+            Interrupt::Break(target) => target,
+            Interrupt::Continue(target) => target,
+        };
+        match target {
+            None => true,
+            Some(name) => label.as_ref().and_then(|t| t.identifier()) == Some(name),
+        }
+    }
+}
+
 pub struct Evaluated {
     pub returned: Option<LoxObj>,
+    pub interrupted: Option<Interrupt>,
 }
 
 impl Evaluated {
-    fn nil() -> Self {
-        Evaluated { returned: None }
+    pub fn nil() -> Self {
+        Evaluated {
+            returned: None,
+            interrupted: None,
+        }
     }
     fn returned(returned: LoxObj) -> Self {
         Evaluated {
             returned: Some(returned),
+            interrupted: None,
         }
     }
+    fn interrupted(interrupt: Interrupt) -> Self {
+        Evaluated {
+            returned: None,
+            interrupted: Some(interrupt),
+        }
+    }
+
+    /// Whether the rest of the enclosing program should be skipped.
+    pub fn is_jump(&self) -> bool {
+        self.returned.is_some() || self.interrupted.is_some()
+    }
 }
 
+/// Calls grow the native stack on demand, see `Executor::in_call`, so the limit holds on threads
+/// with any stack size.
+pub const DEFAULT_MAX_CALL_DEPTH: usize = 5000;
+
+/// Native stack a single Lox call may use before the next call checks it again, generous enough
+/// for unoptimized builds.
+const STACK_RED_ZONE: usize = 1024 * 1024;
+
+/// Size of the extra stack segments allocated once less than `STACK_RED_ZONE` is left.
+const STACK_SEGMENT: usize = 16 * 1024 * 1024;
+
 pub struct Executor {
     pub state: State,
+    /// Lox functions being executed, innermost last.
+    pub(super) call_stack: Vec<CallFrame>,
+    /// Calls nested deeper than this raise a stack overflow error.
+    pub max_call_depth: usize,
+    pub limits: Limits,
+    pub heap: Heap,
+    pub capabilities: Capabilities,
+    pub natives: Registry,
+    /// Where `print` writes to, stdout by default.
+    pub output: Box<dyn Write>,
+    /// When the executor was created, for `clock()`.
+    pub(super) started: Instant,
 }
 
 impl Executor {
     pub fn new() -> Self {
-        Executor {
-            state: State::new(),
-        }
+        Executor::from(State::new())
     }
 
+    /// Executor with the built-in functions registered.
     pub fn from(state: State) -> Self {
-        Executor { state }
+        let mut executor = Executor {
+            state,
+            call_stack: Vec::new(),
+            max_call_depth: DEFAULT_MAX_CALL_DEPTH,
+            limits: Limits::default(),
+            heap: Heap::default(),
+            capabilities: Capabilities::none(),
+            started: Instant::now(),
+            natives: Registry::default(),
+            output: Box::new(io::stdout()),
+        };
+        inbuilt::register_all(&mut executor);
+        executor
+    }
+
+    /// Does F with `function` called at `call_site` on top of the call stack. Errors coming out of
+    /// F get the call stack attached, unless a deeper call already did that.
+    ///
+    /// F runs on a fresh stack segment when the native stack is about to run out, so deep
+    /// recursion ends with the catchable stack overflow error rather than crashing the process.
+    pub fn in_call<F, A>(&mut self, function: &Token, call_site: Span, f: F) -> LoxResult<A>
+    where
+        F: FnOnce(&mut Self) -> LoxResult<A>,
+    {
+        let name = match function.identifier() {
+            Some(name) => name.clone(),
+            None => format!("{:?}", function.val),
+        };
+        self.tick(call_site)?;
+        if self.call_stack.len() >= self.max_call_depth {
+            let mut err = ErrBuilder::new()
+                .of_type(ErrType::RuntimeError)
+                .at(call_site)
+                .with_message(format!(
+                    "Stack overflow: more than {} nested calls while calling {}",
+                    self.max_call_depth, name
+                ))
+                .build();
+            err.stack = self.call_stack.clone();
+            return Err(err);
+        }
+        self.call_stack.push(CallFrame {
+            function: name,
+            call_site,
+        });
+
+        let result = stacker::maybe_grow(STACK_RED_ZONE, STACK_SEGMENT, || f(self));
+        let result = result.map_err(|mut err| {
+            if err.stack.is_empty() {
+                err.stack = self.call_stack.clone();
+            }
+            err
+        });
+
+        self.call_stack.pop();
+        result
     }
 
     /// Creates new scope, does F, pops last scope. Generally used every time the executor goes into curly brackets.
     pub fn scoped<F, A>(&mut self, f: F) -> A
     where
-        F: Fn(&mut Self) -> A,
+        F: FnOnce(&mut Self) -> A,
     {
         self.state.push_new_scope();
         let result = f(self);
@@ -68,12 +191,12 @@ impl Executor {
 impl Visitor<Program, LoxResult<Evaluated>> for Executor {
     fn visit(&mut self, p: &Program) -> LoxResult<Evaluated> {
         for stmt in p.iter() {
-            let evaluated_stmt = self.visit(stmt)?;
-            if evaluated_stmt.returned.is_some() {
-                return Ok(Evaluated::from(evaluated_stmt));
+            let evaluated_stmt = self.visit(&stmt.statement)?;
+            if evaluated_stmt.is_jump() {
+                return Ok(evaluated_stmt);
             }
         }
-        Ok(Evaluated { returned: None })
+        Ok(Evaluated::nil())
     }
 }
 
@@ -85,7 +208,13 @@ impl Visitor<Statement, LoxResult<Evaluated>> for Executor {
             }
             Statement::Print(expr) => {
                 let evaluated = self.visit(expr)?;
-                println!("{}", evaluated.to_string())
+                writeln!(self.output, "{}", evaluated.to_string()).map_err(|err| {
+                    ErrBuilder::new()
+                        .of_type(ErrType::RuntimeError)
+                        .at(expr.span())
+                        .with_message(format!("Could not print: {}", err))
+                        .build()
+                })?;
             }
             Statement::If(cond, program) => {
                 let condition = self.visit(cond)?;
@@ -99,13 +228,31 @@ impl Visitor<Statement, LoxResult<Evaluated>> for Executor {
                     Some(false) => return Ok(Evaluated::nil()),
                     None => {
                         return eval_err()
-                            .at(locate(&cond))
+                            .at(cond.span())
                             .with_message("could not evaluate if stmt condition".to_string())
                             .to_result()
                     }
                 }
             }
-            Statement::WhileLoop(cond, program) => loop {
+            Statement::Block(program) => return self.scoped(|v| v.visit(program)),
+            Statement::ForIn(label, variable, iterable, program) => {
+                let pos = iterable.span();
+                let iterable = self.visit(iterable)?;
+                let iterator = self.iterator_of(&iterable, pos)?;
+
+                while let Some(item) = self.next_item(&iterator, pos)? {
+                    self.tick(pos)?;
+                    let evaluated_program = self.scoped(|v| {
+                        v.state.declare(variable.clone(), item)?;
+                        v.visit(program)
+                    })?;
+                    if let Some(exit) = loop_exit(evaluated_program, label) {
+                        return Ok(exit);
+                    }
+                }
+            }
+            Statement::WhileLoop(label, cond, program) => loop {
+                self.tick(cond.span())?;
                 let loop_condition = self.visit(cond)?;
                 let can_continue_loop = match loop_condition {
                     Plain(val) =>Some(bool::from(val)),
@@ -115,92 +262,126 @@ impl Visitor<Statement, LoxResult<Evaluated>> for Executor {
                 match can_continue_loop {
                     Some(true) => {
                         let evaluated_program = self.scoped(|v| v.visit(program))?;
-                        if evaluated_program.returned.is_some() {
-                            return Ok(evaluated_program);
+                        if let Some(exit) = loop_exit(evaluated_program, label) {
+                            return Ok(exit);
                         }
                     }
-                    Some(false) => return Ok(Evaluated { returned: None }),
+                    Some(false) => return Ok(Evaluated::nil()),
                     None => {
                         return eval_err()
-                            .without_pos()
+                            .at(cond.span())
                             .with_message("could not evaluate while stmt condition".to_string())
                             .to_result()
                     }
                 }
             },
             Statement::Let(lval, rval) => {
-                let pos = locate(&rval.expr);
                 let right_evaluated = self.visit(&rval.expr)?;
-                self.state.bind(lval.identifier.clone(), right_evaluated);
+                self.allocate(stored_size(&right_evaluated), lval.identifier.span)?;
+                self.state.bind(lval.identifier.clone(), right_evaluated)?;
+            }
+            Statement::SetIndex(index_target, rval) => {
+                let target = self.visit(&index_target.target)?;
+                let index = self.visit(&index_target.index)?;
+                let value = self.visit(&rval.expr)?;
+                // may add an entry to a map, charged like `push` and `insert`
+                let added = match target {
+                    LoxObj::Map(_) => MAP_ENTRY - SLOT + stored_size(&value),
+                    _ => stored_size(&value),
+                };
+                self.allocate(added, index_target.span)?;
+                set_index(&target, &index, value, index_target.span)?;
+            }
+            Statement::SetProperty(property_target, rval) => {
+                let target = self.visit(&property_target.target)?;
+                let value = self.visit(&rval.expr)?;
+                match &target {
+                    LoxObj::Object(_) => target.set(property_target.name.clone(), value)?,
+                    LoxObj::Host(host) => {
+                        let name = property_target.name.as_identifier()?;
+                        let mut ctx = Ctx { executor: self, pos: property_target.name.span };
+                        host.set_property(&mut ctx, name, value)?;
+                    }
+                    _ => {
+                        return eval_err()
+                            .at(property_target.name.span)
+                            .with_message(format!("Cannot set properties on {}", target.to_string()))
+                            .to_result()
+                    }
+                }
             }
             Statement::Fun(pos, function_definition) => {
                 self.state.bind(
                     function_definition.name.clone(),
                     LoxObj::Fun(function_definition.clone()),
-                );
+                )?;
+            }
+            Statement::Yield(pos, _) => {
+                // generators step through their bodies on their own, see `Executor::resume`
+                return eval_err()
+                    .at(*pos)
+                    .with_message("Yield outside of a generator".to_string())
+                    .to_result()
+            }
+            Statement::Throw(pos, expr) => {
+                let value = self.visit(expr)?;
+                return self.throw(value, *pos);
             }
+            Statement::Try(try_stmt) => return self.try_catch(try_stmt),
             Statement::Class(defn) => {
                 let class_obj = LoxObj::Class(defn.clone());
-                self.state.bind(defn.name.clone(), class_obj);
+                self.state.bind(defn.name.clone(), class_obj)?;
             }
             Statement::Return(expr) => {
                 let evaluated_expr = self.visit(expr)?;
-                return Ok(Evaluated {
-                    returned: Some(evaluated_expr),
-                });
+                return Ok(Evaluated::returned(evaluated_expr));
+            }
+            Statement::Break(_, label) => {
+                let target = label.as_ref().and_then(|t| t.identifier()).cloned();
+                return Ok(Evaluated::interrupted(Interrupt::Break(target)));
+            }
+            Statement::Continue(_, label) => {
+                let target = label.as_ref().and_then(|t| t.identifier()).cloned();
+                return Ok(Evaluated::interrupted(Interrupt::Continue(target)));
             }
         }
-        Ok(Evaluated { returned: None })
+        Ok(Evaluated::nil())
     }
 }
 
 impl Visitor<Expr, LoxResult<LoxObj>> for Executor {
     fn visit(&mut self, expr: &Expr) -> LoxResult<LoxObj> {
         match expr {
-            Expr::Eqlty(eqlty) => self.visit(eqlty),
-            _ => panic!("call expr is not supported anymore"),
+            Expr::Eqlty(eqlty) => self.visit(eqlty.as_ref()),
+            Expr::Call(callee, _) => eval_err()
+                .at(callee.span)
+                .with_message("Call expressions are not supported anymore".to_string())
+                .to_result(),
         }
     }
 }
 
 impl Visitor<Eqlty, LoxResult<LoxObj>> for Executor {
     fn visit(&mut self, eqlty: &Eqlty) -> LoxResult<LoxObj> {
-        let first_evaluated: LoxResult<LoxObj> = self.visit(&eqlty.first);
-        eqlty
-            .rest
-            .iter()
-            .map(|(op, a)| (op, self.visit(a)))
-            .fold(first_evaluated, eval_fold)
+        self.visit_chain(&eqlty.first, &eqlty.rest)
     }
 }
 
 impl Visitor<Comp, LoxResult<LoxObj>> for Executor {
     fn visit(&mut self, comp: &Comp) -> LoxResult<LoxObj> {
-        let first_evaluated = self.visit(&comp.first);
-        comp.rest
-            .iter()
-            .map(|(op, a)| (op, self.visit(a)))
-            .fold(first_evaluated, eval_fold)
+        self.visit_chain(&comp.first, &comp.rest)
     }
 }
 
 impl Visitor<Term, LoxResult<LoxObj>> for Executor {
     fn visit(&mut self, term: &Term) -> LoxResult<LoxObj> {
-        let first_evaluated = self.visit(&term.first);
-        term.rest
-            .iter()
-            .map(|(op, a)| (op, self.visit(a)))
-            .fold(first_evaluated, eval_fold)
+        self.visit_chain(&term.first, &term.rest)
     }
 }
 
 impl Visitor<Factor, LoxResult<LoxObj>> for Executor {
     fn visit(&mut self, fac: &Factor) -> LoxResult<LoxObj> {
-        let first_evaluated = self.visit(&fac.first);
-        fac.rest
-            .iter()
-            .map(|(op, a)| (op, self.visit(a)))
-            .fold(first_evaluated, eval_fold)
+        self.visit_chain(&fac.first, &fac.rest)
     }
 }
 
@@ -213,53 +394,135 @@ impl Visitor<Unary, LoxResult<LoxObj>> for Executor {
                 let transformed = unary_op(op, &lox_obj)?;
                 Ok(transformed)
             }
-            Unary::Recursive(None, expr) => self.visit(expr.as_ref()),
-            Unary::Recursive(Some(op), expr) => {
+            Unary::Recursive(None, expr, _) => self.visit(expr.as_ref()),
+            Unary::Recursive(Some(op), expr, _) => {
                 let result = self.visit(expr.as_ref())?;
                 let transformed = unary_op(op, &result)?;
                 Ok(transformed)
             }
-            Unary::Call(operator, fn_name, args) => {
+            Unary::Call(operator, fn_name, args, span) => {
                 let func = self.as_lox_obj(fn_name)?;
-                let pos = fn_name.pos;
                 let args_evaluated: LoxResult<Vec<LoxObj>> = args
                     .into_iter()
                     .map(|arg_expr| self.visit(arg_expr.as_ref()))
                     .collect();
 
-                let fn_output = self.call(&func, args_evaluated?, pos)?;
+                let fn_output = self.call(&func, args_evaluated?, *span)?;
                 let result = match operator {
                     Some(op) => unary_op(op, &fn_output)?,
                     None => fn_output,
                 };
                 Ok(result)
             }
+            Unary::List(operator, span, items) => {
+                let items_evaluated: LoxResult<Vec<LoxObj>> =
+                    items.iter().map(|item| self.visit(item)).collect();
+                let list = LoxObj::list(items_evaluated?);
+                self.allocate(allocated_size(&list), *span)?;
+                match operator {
+                    Some(op) => unary_op(op, &list),
+                    None => Ok(list),
+                }
+            }
+            Unary::Map(operator, span, entries) => {
+                let mut entries_evaluated = Vec::new();
+                for (key, value) in entries {
+                    entries_evaluated.push((self.visit(key)?, self.visit(value)?));
+                }
+                let map = inbuilt::map_from(entries_evaluated, *span)?;
+                self.allocate(allocated_size(&map), *span)?;
+                match operator {
+                    Some(op) => unary_op(op, &map),
+                    None => Ok(map),
+                }
+            }
+            Unary::Get(operator, target, name) => {
+                let target = self.visit(target.as_ref())?;
+                let property = self.get_property(&target, name)?;
+                match operator {
+                    Some(op) => unary_op(op, &property),
+                    None => Ok(property),
+                }
+            }
+            Unary::MethodCall(operator, target, name, args, span) => {
+                let target = self.visit(target.as_ref())?;
+                let args_evaluated: LoxResult<Vec<LoxObj>> =
+                    args.iter().map(|arg| self.visit(arg.as_ref())).collect();
+                let method_name = name.as_identifier()?;
+                let output = self.call_method(&target, method_name, args_evaluated?, *span)?;
+                match operator {
+                    Some(op) => unary_op(op, &output),
+                    None => Ok(output),
+                }
+            }
+            Unary::Index(operator, target, span, index) => {
+                let target = self.visit(target.as_ref())?;
+                let index = self.visit(index.as_ref())?;
+                let item = get_index(&target, &index, *span)?;
+                match operator {
+                    Some(op) => unary_op(op, &item),
+                    None => Ok(item),
+                }
+            }
         }
     }
 }
 
-fn eval_fold(acc: LoxResult<LoxObj>, next: (&Token, LoxResult<LoxObj>)) -> LoxResult<LoxObj> {
-    let (op, val) = next;
-    binary_operations::handle(op, acc?, val?)
+/// Decides what a loop does after evaluating its body: `None` means the loop goes on, otherwise
+/// the loop ends and the statement evaluates to the returned value.
+fn loop_exit(evaluated_program: Evaluated, label: &Option<Token>) -> Option<Evaluated> {
+    match &evaluated_program.interrupted {
+        Some(interrupt) if interrupt.targets(label) => match interrupt {
+            Interrupt::Break(_) => return Some(Evaluated::nil()),
+            Interrupt::Continue(_) => return None,
+        },
+        _ => {}
+    }
+
+    match evaluated_program.is_jump() {
+        true => Some(evaluated_program),
+        false => None,
+    }
 }
 
 impl Executor {
+    /// Evaluates a chain of binary operations from left to right. Errors cover the operands
+    /// combined so far.
+    fn visit_chain<A, B>(&mut self, first: &A, rest: &[(Token, B)]) -> LoxResult<LoxObj>
+    where
+        Self: Visitor<A, LoxResult<LoxObj>> + Visitor<B, LoxResult<LoxObj>>,
+        A: Spanned,
+        B: Spanned,
+    {
+        let mut acc = self.visit(first)?;
+        for (op, operand) in rest {
+            let val = self.visit(operand)?;
+            let span = first.span().to(operand.span());
+            acc = binary_operations::handle(op, acc, val, span)?;
+        }
+        Ok(acc)
+    }
+
     /// Evaluates token to `LoxObj` if token is an identifier or value
     fn as_lox_obj(&self, token: &Token) -> LoxResult<LoxObj> {
         match &token.val {
             TokenValue::Id(id) => self.state.get(token),
+            TokenValue::Kwd(Kwd::This) => {
+                let this = Token::new(TokenValue::Id("this".to_string()), token.span);
+                self.state.get(&this)
+            }
             TokenValue::Val(lox_val) => Ok(LoxObj::Plain(lox_val.clone())),
             _ => Err(eval_err()
-                .at(token.pos)
-                .is_not(token, "a lox object")
+                .at(token.span)
+                .is_not(&token.val, "a lox object")
                 .build()),
         }
     }
 
-    fn call(&mut self, func: &LoxObj, args: Vec<LoxObj>, pos: Position) -> LoxResult<LoxObj> {
+    pub fn call(&mut self, func: &LoxObj, args: Vec<LoxObj>, pos: Span) -> LoxResult<LoxObj> {
         match func {
-            LoxObj::Fun(function_def) => self.call_function(function_def, args, pos),
-            LoxObj::Inbuilt(fn_name) => self.call_inbuilt(fn_name, args, pos),
+            LoxObj::Fun(function_def) => self.call_function(function_def, None, args, pos),
+            LoxObj::Native(function) => self.call_native(function, args, pos),
             LoxObj::Class(class_def) => self.call_constructor(class_def, args, pos),
             _ => eval_err()
                 .at(pos)
@@ -268,35 +531,68 @@ impl Executor {
         }
     }
 
-    fn call_constructor(&self, class_def: &ClassDefinition, args: Vec<LoxObj>, pos: Position) -> LoxResult<LoxObj> {
-        let mut obj = LoxObj::Object(HashMap::new());
-        for (field_name, obj) in zip(class_def.fields.clone(), args) {
-            // obj.set(field_name, obj)
-        };
-        unimplemented!()
+    /// Built-in iterables are turned into native iterators, instances have to provide `iter()`.
+    pub(super) fn iterator_of(&mut self, iterable: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+        match iterable {
+            LoxObj::Object(_) => self.call_method(iterable, "iter", Vec::new(), pos),
+            _ => inbuilt::native_iter(iterable, pos),
+        }
+    }
+
+    /// Advances `iterator`. Instances are advanced by calling their `next()` until it returns `nil`.
+    pub(super) fn next_item(&mut self, iterator: &LoxObj, pos: Span) -> LoxResult<Option<LoxObj>> {
+        match iterator {
+            LoxObj::Iterator(native) => Ok(native.borrow_mut().next()),
+            LoxObj::Generator(generator) => self.resume(generator, pos),
+            _ => match self.call_method(iterator, "next", Vec::new(), pos)? {
+                Plain(LoxValue::Nil) => Ok(None),
+                item => Ok(Some(item)),
+            },
+        }
     }
 
-    fn call_function(&mut self, function: &FunctionDefinition, args: Vec<LoxObj>, pos: Position) -> LoxResult<LoxObj> {
+    /// Runs the function body with its arguments, and `this` for methods, bound in a new scope.
+    /// Generators only get their scope prepared, the body runs when they are resumed.
+    pub(super) fn call_function(&mut self, function: &FunctionDefinition, this: Option<LoxObj>, args: Vec<LoxObj>, pos: Span) -> LoxResult<LoxObj> {
         self.state.push_new_scope();
 
-        for (fn_arg, fn_arg_val) in zip(function.args.clone(), args) {
-            self.state.bind(fn_arg, fn_arg_val);
+        let bound = (|| {
+            if let Some(this) = this {
+                self.state.declare(Token::new(TokenValue::Id("this".to_string()), pos), this)?;
+            }
+            for (fn_arg, fn_arg_val) in zip(function.args.clone(), args) {
+                self.state.declare(fn_arg, fn_arg_val)?;
+            }
+            Ok(())
+        })();
+        if let Err(err) = bound {
+            self.state.pop_last_scope();
+            return Err(err);
+        }
+
+        if function.is_generator {
+            let scope = match self.state.split_off_scopes(self.state.depth() - 1).pop() {
+                Some(scope) => scope,
+                None => {
+                    return eval_err()
+                        .at(pos)
+                        .with_message(format!("Lost the scope of {}", function.name))
+                        .to_result()
+                }
+            };
+            let generator = Generator::new(function, scope);
+            let generator = LoxObj::Generator(Rc::new(RefCell::new(generator)));
+            self.allocate(allocated_size(&generator), pos)?;
+            return Ok(generator);
         }
 
-        let program_result = self.visit(&function.body)?;
+        let program_result = self.in_call(&function.name, pos, |v| v.visit(&function.body));
 
         self.state.pop_last_scope();
 
-        match program_result.returned {
+        match program_result?.returned {
             Some(obj) => Ok(obj),
             None => Ok(LoxObj::Plain(LoxValue::from(0))),
         }
     }
-
-    fn call_inbuilt(&mut self, fn_name: &String, args: Vec<LoxObj>, pos: Position) -> LoxResult<LoxObj> {
-        if fn_name.eq("mod") {
-            return modulo(args[0].clone(), args[1].clone(), pos);
-        };
-        panic!()
-    }
 }
@@ -0,0 +1,403 @@
+//! Resumable execution of generator functions.
+//!
+//! The executor evaluates statements by recursing through Rust frames, which can't be suspended.
+//! Instead, a generator keeps its own stack of `Frame`s - cursors into the blocks it is currently
+//! in - together with the scopes belonging to them. Compound statements are entered by pushing a
+//! frame, while every other statement is evaluated by the executor as usual, so a `yield` at any
+//! depth of the generator body only has to save the frames and scopes.
+//!
+//! `try` statements are frames as well. Errors raised inside of the generator unwind its frames
+//! to the innermost `try` which handles them, while `return`, `break` and `continue` stop at every
+//! pending `finally` block on their way out. The frame of a `finally` block remembers how the
+//! block was entered, so it can carry on from there once the block is done.
+
+use std::fmt;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{LoxError, LoxResult};
+use crate::interpreter::execute::definitions::{LoxGenerator, LoxObj};
+use crate::interpreter::execute::exceptions::caught_value;
+use crate::interpreter::execute::executing::{Executor, Interrupt};
+use crate::interpreter::execute::operations::eval_err;
+use crate::interpreter::execute::state::Scope;
+use crate::interpreter::parser::structure::*;
+use crate::interpreter::parser::visitor::Visitor;
+use crate::interpreter::tokens::Token;
+
+/// Suspended call of a generator function.
+pub struct Generator {
+    name: Token,
+    /// Scopes of the generator, one per frame. Moved onto the executor's stack while it runs.
+    scopes: Vec<Scope>,
+    frames: Vec<Frame>,
+    running: bool,
+}
+
+impl fmt::Debug for Generator {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Generator {:?}", self.name)
+    }
+}
+
+impl Generator {
+    /// `scope` has to contain the arguments of the call.
+    pub fn new(function: &FunctionDefinition, scope: Scope) -> Self {
+        Generator {
+            name: function.name.clone(),
+            scopes: vec![scope],
+            frames: vec![Frame::new(FrameKind::Block, function.body.clone())],
+            running: false,
+        }
+    }
+
+    pub fn name(&self) -> &Token {
+        &self.name
+    }
+
+    /// Values kept alive by the suspended generator.
+    pub fn values(&self) -> impl Iterator<Item = &LoxObj> {
+        let iterators = self.frames.iter().filter_map(|frame| match &frame.kind {
+            FrameKind::ForIn(_, _, iterator, _) => Some(iterator),
+            FrameKind::Finally(Completion::Error(err)) => err.thrown.as_deref(),
+            _ => None,
+        });
+        self.scopes.iter().flat_map(|scope| scope.values()).chain(iterators)
+    }
+}
+
+/// Position inside of a block which is being executed.
+struct Frame {
+    kind: FrameKind,
+    program: Program,
+    next: usize,
+}
+
+enum FrameKind {
+    Block,
+    While(Option<Token>, Expr),
+    ForIn(Option<Token>, Token, LoxObj, Span),
+    /// Body of a `try` statement.
+    Try(TryStatement),
+    /// `catch` block of a `try` statement, which still has to run its `finally` block.
+    Catch(TryStatement),
+    /// `finally` block, which goes on with `Completion` once it is done.
+    Finally(Completion),
+}
+
+/// How a `try` statement was left before its `finally` block ran.
+enum Completion {
+    Normal,
+    Error(LoxError),
+    Return,
+    Jump(Interrupt),
+}
+
+/// Outcome of running one statement of a generator.
+enum Step {
+    Next,
+    Yielded(LoxObj),
+    Finished,
+}
+
+impl Frame {
+    fn new(kind: FrameKind, program: Program) -> Self {
+        Frame {
+            kind,
+            program,
+            next: 0,
+        }
+    }
+
+    fn next_statement(&mut self) -> Option<Statement> {
+        let stmt = self.program.get(self.next).cloned();
+        self.next += 1;
+        stmt.map(|stmt| stmt.statement)
+    }
+
+    /// Whether `interrupt` ends or restarts this frame.
+    fn handles(&self, interrupt: &Interrupt) -> bool {
+        match &self.kind {
+            FrameKind::While(label, _) => interrupt.targets(label),
+            FrameKind::ForIn(label, _, _, _) => interrupt.targets(label),
+            _ => false,
+        }
+    }
+
+    /// `finally` block which has to run before this frame is left.
+    fn pending_finally(&self) -> Option<&Program> {
+        match &self.kind {
+            FrameKind::Try(try_stmt) | FrameKind::Catch(try_stmt) => try_stmt.finally.as_ref(),
+            _ => None,
+        }
+    }
+
+    /// Whether an error raised inside of this frame stops here, to be caught or to run `finally`.
+    fn stops_error(&self) -> bool {
+        match &self.kind {
+            FrameKind::Try(try_stmt) => try_stmt.catch.is_some() || try_stmt.finally.is_some(),
+            FrameKind::Catch(try_stmt) => try_stmt.finally.is_some(),
+            _ => false,
+        }
+    }
+}
+
+impl Executor {
+    /// Runs the generator until its next `yield`. Returns `None` once the generator is finished.
+    pub fn resume(&mut self, generator: &LoxGenerator, pos: Span) -> LoxResult<Option<LoxObj>> {
+        let (scopes, mut frames) = {
+            let mut generator = generator.borrow_mut();
+            if generator.running {
+                return eval_err()
+                    .at(pos)
+                    .with_message(format!("Generator {} is already running", generator.name))
+                    .to_result();
+            }
+            generator.running = true;
+            let scopes = std::mem::take(&mut generator.scopes);
+            let frames = std::mem::take(&mut generator.frames);
+            (scopes, frames)
+        };
+
+        let name = generator.borrow().name.clone();
+        let base_depth = self.state.depth();
+        self.state.restore_scopes(scopes);
+        let result = self.in_call(&name, pos, |v| v.run_frames(&mut frames));
+        let scopes = self.state.split_off_scopes(base_depth);
+
+        let mut generator = generator.borrow_mut();
+        generator.running = false;
+        // once finished or failed, the generator can't be resumed
+        if let Ok(Some(_)) = result {
+            generator.scopes = scopes;
+            generator.frames = frames;
+        }
+        result
+    }
+
+    fn run_frames(&mut self, frames: &mut Vec<Frame>) -> LoxResult<Option<LoxObj>> {
+        loop {
+            match self.step(frames) {
+                Ok(Step::Next) => {}
+                Ok(Step::Yielded(value)) => return Ok(Some(value)),
+                Ok(Step::Finished) => return Ok(None),
+                Err(err) => self.catch_in_frames(frames, err)?,
+            }
+        }
+    }
+
+    fn step(&mut self, frames: &mut Vec<Frame>) -> LoxResult<Step> {
+        let frame = match frames.last_mut() {
+            Some(frame) => frame,
+            None => return Ok(Step::Finished),
+        };
+
+        let stmt = match frame.next_statement() {
+            Some(stmt) => stmt,
+            None => return self.end_of_frame(frames),
+        };
+
+        match stmt {
+            Statement::Yield(_, expr) => return Ok(Step::Yielded(self.visit(&expr)?)),
+            Statement::Return(expr) => {
+                self.visit(&expr)?;
+                return Ok(self.return_from(frames));
+            }
+            Statement::Block(program) => self.enter(frames, FrameKind::Block, program),
+            Statement::If(cond, program) => {
+                if self.condition(&cond, "if")? {
+                    self.enter(frames, FrameKind::Block, program)
+                }
+            }
+            Statement::WhileLoop(label, cond, program) => {
+                if self.condition(&cond, "while")? {
+                    self.enter(frames, FrameKind::While(label, cond), program)
+                }
+            }
+            Statement::ForIn(label, variable, iterable, program) => {
+                let pos = iterable.span();
+                let iterable = self.visit(&iterable)?;
+                let iterator = self.iterator_of(&iterable, pos)?;
+                if let Some(item) = self.next_item(&iterator, pos)? {
+                    let kind = FrameKind::ForIn(label, variable.clone(), iterator, pos);
+                    self.enter(frames, kind, program);
+                    self.state.declare(variable, item)?;
+                }
+            }
+            Statement::Try(try_stmt) => {
+                let body = try_stmt.body.clone();
+                self.enter(frames, FrameKind::Try(try_stmt), body);
+            }
+            Statement::Break(_, label) => {
+                let target = label.as_ref().and_then(|t| t.identifier()).cloned();
+                self.jump(frames, Interrupt::Break(target));
+            }
+            Statement::Continue(_, label) => {
+                let target = label.as_ref().and_then(|t| t.identifier()).cloned();
+                self.jump(frames, Interrupt::Continue(target));
+            }
+            stmt => {
+                let evaluated = self.visit(&stmt)?;
+                if evaluated.returned.is_some() {
+                    return Ok(self.return_from(frames));
+                }
+                if let Some(interrupt) = evaluated.interrupted {
+                    self.jump(frames, interrupt);
+                }
+            }
+        }
+        Ok(Step::Next)
+    }
+
+    /// Unwinds the frames to the innermost `try` which handles `err`, and enters its `catch` or
+    /// `finally` block. Errors which no frame handles, and those which can't be caught, are returned.
+    fn catch_in_frames(&mut self, frames: &mut Vec<Frame>, err: LoxError) -> LoxResult<()> {
+        if !err.err_type.is_catchable() {
+            return Err(err);
+        }
+        let handled_at = match frames.iter().rposition(Frame::stops_error) {
+            Some(handled_at) => handled_at,
+            None => return Err(err),
+        };
+
+        self.unwind(frames, frames.len() - handled_at - 1);
+        self.state.pop_last_scope();
+        let (try_stmt, in_body) = match frames.pop().map(|frame| frame.kind) {
+            Some(FrameKind::Try(try_stmt)) => (try_stmt, true),
+            Some(FrameKind::Catch(try_stmt)) => (try_stmt, false),
+            _ => return Err(err),
+        };
+        match (&try_stmt.catch, in_body) {
+            (Some((error_var, handler)), true) => {
+                let (error_var, handler) = (error_var.clone(), handler.clone());
+                self.enter(frames, FrameKind::Catch(try_stmt), handler);
+                self.state.declare(error_var, caught_value(&err))?;
+            }
+            _ => {
+                let cleanup = try_stmt.finally.unwrap_or_default();
+                self.enter(frames, FrameKind::Finally(Completion::Error(err)), cleanup);
+            }
+        }
+        Ok(())
+    }
+
+    /// Leaves the generator, running the pending `finally` blocks first.
+    fn return_from(&mut self, frames: &mut Vec<Frame>) -> Step {
+        let pending = frames.iter().rposition(|f| f.pending_finally().is_some());
+        match pending {
+            Some(at) => {
+                self.enter_finally(frames, at, Completion::Return);
+                Step::Next
+            }
+            None => {
+                self.unwind(frames, frames.len());
+                Step::Finished
+            }
+        }
+    }
+
+    /// Leaves the frames up to the loop targeted by `interrupt`. For `continue` the loop is then
+    /// moved to the end of its body, so it starts the next iteration. Pending `finally` blocks
+    /// inside of the loop run first.
+    fn jump(&mut self, frames: &mut Vec<Frame>, interrupt: Interrupt) {
+        let handled_at = frames.iter().rposition(|f| f.handles(&interrupt)).unwrap_or(0);
+        let pending = frames.iter().rposition(|f| f.pending_finally().is_some());
+        if let Some(at) = pending.filter(|at| *at > handled_at) {
+            return self.enter_finally(frames, at, Completion::Jump(interrupt));
+        }
+
+        match interrupt {
+            Interrupt::Break(_) => self.unwind(frames, frames.len() - handled_at),
+            Interrupt::Continue(_) => {
+                self.unwind(frames, frames.len() - handled_at - 1);
+                if let Some(frame) = frames.last_mut() {
+                    frame.next = frame.program.len();
+                }
+            }
+        }
+    }
+
+    /// Replaces the frames from `at` on by the `finally` block of the frame at `at`.
+    fn enter_finally(&mut self, frames: &mut Vec<Frame>, at: usize, completion: Completion) {
+        let cleanup = frames[at].pending_finally().cloned().unwrap_or_default();
+        self.unwind(frames, frames.len() - at);
+        self.enter(frames, FrameKind::Finally(completion), cleanup);
+    }
+
+    fn enter(&mut self, frames: &mut Vec<Frame>, kind: FrameKind, program: Program) {
+        self.state.push_new_scope();
+        frames.push(Frame::new(kind, program));
+    }
+
+    /// Pops `n` innermost frames together with their scopes.
+    fn unwind(&mut self, frames: &mut Vec<Frame>, n: usize) {
+        for _ in 0..n {
+            frames.pop();
+            self.state.pop_last_scope();
+        }
+    }
+
+    /// Called once the innermost frame has run all its statements. Blocks are left, loops start
+    /// their next iteration in a fresh scope, `try` statements go on with their `finally` block
+    /// and `finally` blocks with whatever entered them.
+    fn end_of_frame(&mut self, frames: &mut Vec<Frame>) -> LoxResult<Step> {
+        // the frame is left first, so errors raised below are not handled by it
+        let mut frame = match frames.pop() {
+            Some(frame) => frame,
+            None => return Ok(Step::Finished),
+        };
+        self.state.pop_last_scope();
+
+        let next_item = match &frame.kind {
+            FrameKind::Block => None,
+            FrameKind::While(_, cond) => {
+                let cond = cond.clone();
+                self.tick(cond.span())?;
+                match self.condition(&cond, "while")? {
+                    true => Some(None),
+                    false => None,
+                }
+            }
+            FrameKind::ForIn(_, variable, iterator, pos) => {
+                let (variable, iterator, pos) = (variable.clone(), iterator.clone(), *pos);
+                self.tick(pos)?;
+                self.next_item(&iterator, pos)?.map(|item| Some((variable, item)))
+            }
+            FrameKind::Try(try_stmt) | FrameKind::Catch(try_stmt) => {
+                if let Some(cleanup) = &try_stmt.finally {
+                    self.enter(frames, FrameKind::Finally(Completion::Normal), cleanup.clone());
+                }
+                None
+            }
+            FrameKind::Finally(_) => None,
+        };
+
+        if let Some(binding) = next_item {
+            frame.next = 0;
+            self.enter(frames, frame.kind, frame.program);
+            if let Some((variable, item)) = binding {
+                self.state.declare(variable, item)?;
+            }
+            return Ok(Step::Next);
+        }
+
+        match frame.kind {
+            FrameKind::Finally(Completion::Error(err)) => Err(err),
+            FrameKind::Finally(Completion::Return) => Ok(self.return_from(frames)),
+            FrameKind::Finally(Completion::Jump(interrupt)) => {
+                self.jump(frames, interrupt);
+                Ok(Step::Next)
+            }
+            _ => Ok(Step::Next),
+        }
+    }
+
+    fn condition(&mut self, cond: &Expr, stmt_name: &str) -> LoxResult<bool> {
+        match self.visit(cond)? {
+            LoxObj::Plain(val) => Ok(bool::from(val)),
+            _ => eval_err()
+                .at(cond.span())
+                .with_message(format!("could not evaluate {} stmt condition", stmt_name))
+                .to_result(),
+        }
+    }
+}
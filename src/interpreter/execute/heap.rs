@@ -0,0 +1,199 @@
+//! Approximate accounting of the memory held by live values.
+//!
+//! Values are reference counted, so nothing records when they die. Instead, `heap_stats` walks
+//! everything reachable from the scopes and measures it. That is too slow to do on every
+//! allocation, so allocations are only summed up, and the heap is measured once they could have
+//! used up the room left under the limit.
+
+use std::collections::HashSet;
+use std::mem::size_of;
+use std::rc::Rc;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxResult};
+use crate::interpreter::execute::definitions::{LoxObj, NativeIterator, OrderedMap};
+use crate::interpreter::execute::executing::Executor;
+use crate::interpreter::parser::structure::{ClassDefinition, FunctionDefinition};
+use crate::interpreter::tokens::LoxValue;
+
+/// Size of a value stored in a binding, list, map or field.
+pub(super) const SLOT: usize = size_of::<LoxObj>();
+
+/// Size of an entry of a map, whose key is stored twice.
+pub(super) const MAP_ENTRY: usize = SLOT + 2 * size_of::<LoxValue>();
+
+#[derive(Default)]
+pub struct Heap {
+    /// Live values may take at most about this many bytes.
+    pub max_bytes: Option<usize>,
+    /// Bytes allocated since the heap was last measured.
+    unchecked: usize,
+    /// How many bytes can be allocated before the heap has to be measured again.
+    headroom: usize,
+}
+
+/// Live values by kind, with their approximate size.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct HeapStats {
+    pub bytes: usize,
+    pub strings: usize,
+    pub lists: usize,
+    pub maps: usize,
+    pub objects: usize,
+    pub functions: usize,
+    pub classes: usize,
+    pub iterators: usize,
+    pub generators: usize,
+    pub host_objects: usize,
+}
+
+impl HeapStats {
+    /// Adds `obj` and everything reachable from it, except shared values in `visited`.
+    fn add(&mut self, obj: &LoxObj, visited: &mut HashSet<usize>) {
+        self.bytes += SLOT;
+        match obj {
+            LoxObj::Plain(val) => self.add_value(val),
+            LoxObj::Native(_) => {}
+            LoxObj::Host(host) => {
+                if visited.insert(Rc::as_ptr(host) as *const () as usize) {
+                    self.host_objects += 1;
+                }
+            }
+            LoxObj::Fun(_) => {
+                self.functions += 1;
+                self.bytes += size_of::<FunctionDefinition>();
+            }
+            LoxObj::Class(_) => {
+                self.classes += 1;
+                self.bytes += size_of::<ClassDefinition>();
+            }
+            LoxObj::List(items) => {
+                if visited.insert(Rc::as_ptr(items) as *const () as usize) {
+                    self.lists += 1;
+                    for item in items.borrow().iter() {
+                        self.add(item, visited);
+                    }
+                }
+            }
+            LoxObj::Map(map) => {
+                if visited.insert(Rc::as_ptr(map) as *const () as usize) {
+                    self.maps += 1;
+                    self.add_map(&map.borrow(), visited);
+                }
+            }
+            LoxObj::Object(instance) => {
+                if visited.insert(Rc::as_ptr(instance) as *const () as usize) {
+                    self.objects += 1;
+                    for (name, field) in instance.borrow().fields.iter() {
+                        self.bytes += name.len();
+                        self.add(field, visited);
+                    }
+                }
+            }
+            LoxObj::Iterator(iterator) => {
+                if visited.insert(Rc::as_ptr(iterator) as *const () as usize) {
+                    self.iterators += 1;
+                    match &*iterator.borrow() {
+                        NativeIterator::List { items, .. } => {
+                            self.add(&LoxObj::List(items.clone()), visited)
+                        }
+                        NativeIterator::Snapshot { items, .. } => {
+                            for item in items {
+                                self.add(item, visited);
+                            }
+                        }
+                        NativeIterator::Range { .. } => {}
+                    }
+                }
+            }
+            LoxObj::Generator(generator) => {
+                if visited.insert(Rc::as_ptr(generator) as *const () as usize) {
+                    self.generators += 1;
+                    for value in generator.borrow().values() {
+                        self.add(value, visited);
+                    }
+                }
+            }
+        }
+    }
+
+    fn add_value(&mut self, val: &LoxValue) {
+        if let LoxValue::String(text) = val {
+            self.strings += 1;
+            self.bytes += text.len();
+        }
+    }
+
+    fn add_map(&mut self, map: &OrderedMap, visited: &mut HashSet<usize>) {
+        for (key, value) in map.iter() {
+            // the key is stored twice, once in the entries and once in the positions
+            self.bytes += 2 * size_of::<LoxValue>() + size_of::<usize>();
+            self.add_value(key);
+            self.add(value, visited);
+        }
+    }
+}
+
+/// Bytes allocated by creating `obj`, not counting values it only refers to.
+pub fn allocated_size(obj: &LoxObj) -> usize {
+    match obj {
+        LoxObj::Plain(LoxValue::String(text)) => text.len(),
+        LoxObj::List(items) => items.borrow().len() * SLOT,
+        LoxObj::Map(map) => map.borrow().len() * MAP_ENTRY,
+        LoxObj::Object(instance) => instance.borrow().fields.len() * SLOT,
+        LoxObj::Iterator(_) | LoxObj::Generator(_) => SLOT,
+        _ => 0,
+    }
+}
+
+/// Bytes taken by storing `obj` in a binding, list, map or field. Plain values like strings are
+/// copied into the slot, everything else is shared.
+pub fn stored_size(obj: &LoxObj) -> usize {
+    match obj {
+        LoxObj::Plain(_) => SLOT + allocated_size(obj),
+        _ => SLOT,
+    }
+}
+
+impl Executor {
+    /// Measures everything reachable from the scopes.
+    pub fn heap_stats(&self) -> HeapStats {
+        let mut stats = HeapStats::default();
+        let mut visited = HashSet::new();
+        for value in self.state.values() {
+            stats.add(value, &mut visited);
+        }
+        stats
+    }
+
+    /// Records an allocation of `bytes` at `pos`, raising an out of memory error if the heap gets
+    /// over its limit.
+    pub(super) fn allocate(&mut self, bytes: usize, pos: Span) -> LoxResult<()> {
+        let max_bytes = match self.heap.max_bytes {
+            Some(max_bytes) => max_bytes,
+            None => return Ok(()),
+        };
+
+        self.heap.unchecked += bytes;
+        if self.heap.unchecked < self.heap.headroom {
+            return Ok(());
+        }
+
+        // the new value is usually not bound yet, so it isn't reachable
+        let live = self.heap_stats().bytes + bytes;
+        self.heap.unchecked = 0;
+        if live > max_bytes {
+            self.heap.headroom = 0;
+            return ErrBuilder::new()
+                .of_type(ErrType::RuntimeError)
+                .at(pos)
+                .with_message(format!(
+                    "Out of memory: live values take about {} bytes, but the limit is {}",
+                    live, max_bytes
+                ))
+                .to_result();
+        }
+        self.heap.headroom = (max_bytes - live).max(max_bytes / 8);
+        Ok(())
+    }
+}
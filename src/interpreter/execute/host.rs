@@ -0,0 +1,40 @@
+//! Rust objects handed to Lox by the host application, e.g. a database handle.
+//!
+//! Scripts use them like instances of classes: `obj.name` reads a property, `obj.name = value`
+//! sets it and `obj.name(args)` calls a method. What these do is up to the host. Everything is
+//! refused by default, with errors naming the host type.
+
+use std::fmt;
+
+use crate::interpreter::errors::LoxResult;
+use crate::interpreter::execute::definitions::LoxObj;
+use crate::interpreter::execute::native::Ctx;
+
+pub trait HostObject {
+    /// Name of the type, used in error messages.
+    fn type_name(&self) -> &str;
+
+    fn get_property(&self, ctx: &mut Ctx, name: &str) -> LoxResult<LoxObj> {
+        Err(ctx.error(format!("{} has no property {}", self.type_name(), name)))
+    }
+
+    /// Takes `&self`, so objects which can be modified need interior mutability.
+    fn set_property(&self, ctx: &mut Ctx, name: &str, _value: LoxObj) -> LoxResult<()> {
+        Err(ctx.error(format!("Cannot set property {} on {}", name, self.type_name())))
+    }
+
+    fn call_method(&self, ctx: &mut Ctx, name: &str, _args: Vec<LoxObj>) -> LoxResult<LoxObj> {
+        Err(ctx.error(format!("{} has no method {}", self.type_name(), name)))
+    }
+
+    /// Text printed for the object.
+    fn display(&self) -> String {
+        format!("<{}>", self.type_name())
+    }
+}
+
+impl fmt::Debug for dyn HostObject {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} {}", self.type_name(), self.display())
+    }
+}
@@ -1,37 +1,388 @@
 use crate::interpreter::execute::inbuilt::LoxObj::Plain;
 use crate::interpreter::tokens::LoxValue::Integer;
-use crate::interpreter::errors::ErrBuilder;
+use crate::interpreter::errors::{span::Span, ErrBuilder, ErrType, LoxError};
 use crate::interpreter::errors::ErrType::RuntimeError;
-use crate::interpreter::parser::locator::locate;
 use crate::interpreter::tokens::LoxValue;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Instant;
 use crate::interpreter::{
-    errors::position::Position,
+
     errors::LoxResult,
     execute::{
         definitions::{LoxObj},
-        operations::{binary_operations, eval_err, unary_op},
+        definitions::{NativeIterator, OrderedMap},
+        capabilities::Capability,
+        executing::Executor,
+        heap::{stored_size, HeapStats},
+        native::NativeFunction,
+        operations::{as_key, as_list, as_map, normalize_index},
     },
-    parser::structure::*,
-    parser::visitor::*,
-    tokens::Token,
-    tokens::TokenValue,
 };
 
-fn runtime_err_at(pos: Position) -> ErrBuilder {
-    ErrBuilder::new().of_type(RuntimeError).at(pos)
-} 
+/// Registers the functions available in every program.
+pub fn register_all(executor: &mut Executor) {
+    let natives = [
+        NativeFunction::new("mod", 2, |ctx, args| modulo(args[0].clone(), args[1].clone(), ctx.pos)),
+        NativeFunction::new("len", 1, |ctx, args| len(&args[0], ctx.pos)),
+        NativeFunction::new("push", 2, |ctx, args| {
+            ctx.executor.allocate(stored_size(&args[1]), ctx.pos)?;
+            push(&args[0], args[1].clone(), ctx.pos)
+        }),
+        NativeFunction::new("pop", 1, |ctx, args| pop(&args[0], ctx.pos)),
+        NativeFunction::new("insert", 3, |ctx, args| {
+            ctx.executor.allocate(stored_size(&args[2]), ctx.pos)?;
+            insert(&args[0], &args[1], args[2].clone(), ctx.pos)
+        }),
+        NativeFunction::new("remove", 2, |ctx, args| remove(&args[0], &args[1], ctx.pos)),
+        NativeFunction::new("slice", 3, |ctx, args| slice(&args[0], &args[1], &args[2], ctx.pos)),
+        NativeFunction::new("contains", 2, |ctx, args| contains(&args[0], &args[1], ctx.pos)),
+        NativeFunction::new("join", 2, |ctx, args| join(&args[0], &args[1], ctx.pos)),
+        NativeFunction::new("keys", 1, |ctx, args| keys(&args[0], ctx.pos)),
+        NativeFunction::new("values", 1, |ctx, args| values(&args[0], ctx.pos)),
+        NativeFunction::new("has", 2, |ctx, args| has(&args[0], &args[1], ctx.pos)),
+        NativeFunction::new("delete", 2, |ctx, args| delete(&args[0], &args[1], ctx.pos)),
+        NativeFunction::new("iter", 1, |ctx, args| ctx.executor.iterator_of(&args[0], ctx.pos)),
+        NativeFunction::new("range", 3, |ctx, args| range(&args[0], &args[1], &args[2], ctx.pos)),
+        NativeFunction::new("heap_stats", 0, |ctx, _| Ok(heap_stats(&ctx.executor.heap_stats()))),
+        NativeFunction::new("read_file", 1, |ctx, args| {
+            let path = path_of(&args[0], ctx.pos)?;
+            ctx.executor.capabilities.check_path(Capability::FsRead, &path, ctx.pos)?;
+            read_file(&path, ctx.pos)
+        })
+        .requires(Capability::FsRead),
+        NativeFunction::new("write_file", 2, |ctx, args| {
+            let path = path_of(&args[0], ctx.pos)?;
+            ctx.executor.capabilities.check_path(Capability::FsWrite, &path, ctx.pos)?;
+            write_file(&path, &args[1], ctx.pos)
+        })
+        .requires(Capability::FsWrite),
+        NativeFunction::new("env", 1, |ctx, args| env(&args[0], ctx.pos)).requires(Capability::Env),
+        NativeFunction::new("clock", 0, |ctx, _| clock(ctx.executor.started)).requires(Capability::Clock),
+        NativeFunction::new("exit", 1, |ctx, args| exit(&args[0], ctx.pos)).requires(Capability::Exit),
+        NativeFunction::new("input", 0, |ctx, _| input(ctx.pos)).requires(Capability::Stdin),
+    ];
 
+    for native in natives {
+        executor.register(native);
+    }
+}
 
-pub fn modulo(a: LoxObj, b: LoxObj, pos: Position) -> LoxResult<LoxObj> {
-    let cast_to_int = |obj: LoxObj| match obj {
-        Plain(Integer(x)) => Ok(x),
+fn runtime_err_at(pos: Span) -> ErrBuilder<ErrType, Span> {
+    ErrBuilder::new().of_type(RuntimeError).at(pos)
+}
+
+fn cast_to_int(obj: &LoxObj, pos: Span) -> LoxResult<i16> {
+    match obj {
+        Plain(Integer(x)) => Ok(*x),
         _ => runtime_err_at(pos).is_not(obj.to_string(), "integer").to_result()
+    }
+}
+
+fn cast_to_string(obj: &LoxObj, pos: Span) -> LoxResult<String> {
+    match obj {
+        Plain(val @ LoxValue::String(_)) => Ok(val.to_string()),
+        _ => runtime_err_at(pos).is_not(obj.to_string(), "string").to_result(),
+    }
+}
+
+fn from_len(len: usize, pos: Span) -> LoxResult<LoxObj> {
+    match i16::try_from(len) {
+        Ok(len) => Ok(Plain(Integer(len))),
+        Err(_) => runtime_err_at(pos)
+            .with_message(format!("Length {} does not fit in an integer", len))
+            .to_result(),
+    }
+}
+
+pub fn modulo(a: LoxObj, b: LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let val_a = cast_to_int(&a, pos)?;
+    let val_b = cast_to_int(&b, pos)?;
+
+    match val_a.checked_rem(val_b) {
+        Some(rem) => Ok(Plain(Integer(rem))),
+        None if val_b == 0 => runtime_err_at(pos)
+            .with_message(format!("Division by zero in mod of {} and {}", val_a, val_b))
+            .to_result(),
+        None => runtime_err_at(pos)
+            .with_message(format!("Integer overflow in mod of {} and {}", val_a, val_b))
+            .to_result(),
+    }
+}
+
+/// Number of items of a list or entries of a map
+pub fn len(collection: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    if let LoxObj::Map(map) = collection {
+        let len = map.borrow().len();
+        return from_len(len, pos);
+    }
+
+    let items = as_list(collection, pos)?;
+    let len = items.borrow().len();
+    from_len(len, pos)
+}
+
+/// Appends `item` and returns the new length
+pub fn push(list: &LoxObj, item: LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    items.borrow_mut().push(item);
+    let len = items.borrow().len();
+    from_len(len, pos)
+}
+
+/// Removes and returns the last item
+pub fn pop(list: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let popped = items.borrow_mut().pop();
+    popped.ok_or(
+        runtime_err_at(pos)
+            .with_message("Cannot pop from an empty list".to_string())
+            .build(),
+    )
+}
+
+/// Inserts `item` so that it ends up at `index` and returns the new length
+pub fn insert(list: &LoxObj, index: &LoxObj, item: LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let index = cast_to_int(index, pos)?;
+    let len = items.borrow().len();
+
+    let i = normalize_index(index, len);
+    if i < 0 || i > len as i64 {
+        return runtime_err_at(pos)
+            .with_message(format!("Cannot insert at {} into list of length {}", index, len))
+            .to_result();
+    }
+
+    items.borrow_mut().insert(i as usize, item);
+    from_len(len + 1, pos)
+}
+
+/// Removes and returns the item at `index`
+pub fn remove(list: &LoxObj, index: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let index = cast_to_int(index, pos)?;
+    let len = items.borrow().len();
+
+    let i = normalize_index(index, len);
+    if i < 0 || i >= len as i64 {
+        return runtime_err_at(pos)
+            .with_message(format!("Index {} is out of range for list of length {}", index, len))
+            .to_result();
+    }
+
+    let removed = items.borrow_mut().remove(i as usize);
+    Ok(removed)
+}
+
+/// Returns a new list with items from `start` up to, but not including, `end`. Out of range bounds are clamped.
+pub fn slice(list: &LoxObj, start: &LoxObj, end: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let items = items.borrow();
+    let len = items.len() as i64;
+    let clamp = |bound: i16| normalize_index(bound, items.len()).clamp(0, len) as usize;
+
+    let start = clamp(cast_to_int(start, pos)?);
+    let end = clamp(cast_to_int(end, pos)?);
+    if start >= end {
+        return Ok(LoxObj::list(Vec::new()));
+    }
+
+    Ok(LoxObj::list(items[start..end].to_vec()))
+}
+
+pub fn contains(list: &LoxObj, item: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let found = items.borrow().iter().any(|x| x.equals(item));
+    Ok(Plain(LoxValue::Boolean(found)))
+}
+
+/// Concatenates the items into a string, putting `separator` between them
+pub fn join(list: &LoxObj, separator: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let items = as_list(list, pos)?;
+    let separator = match separator {
+        Plain(val @ LoxValue::String(_)) => val.to_string(),
+        _ => return runtime_err_at(pos).is_not(separator.to_string(), "string").to_result(),
+    };
+
+    let mut parts = Vec::new();
+    for item in items.borrow().iter() {
+        match item {
+            Plain(val) => parts.push(val.to_string()),
+            _ => return runtime_err_at(pos).is_not(item.to_string(), "a value").to_result(),
+        }
+    }
+
+    Ok(Plain(LoxValue::String(format!("\"{}\"", parts.join(&separator)))))
+}
+
+/// Keys of the map, in insertion order
+pub fn keys(map: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let map = as_map(map, pos)?;
+    let keys = map.borrow().iter().map(|(key, _)| Plain(key.clone())).collect();
+    Ok(LoxObj::list(keys))
+}
+
+/// Values of the map, in insertion order of their keys
+pub fn values(map: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let map = as_map(map, pos)?;
+    let values = map.borrow().iter().map(|(_, value)| value.clone()).collect();
+    Ok(LoxObj::list(values))
+}
+
+pub fn has(map: &LoxObj, key: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let map = as_map(map, pos)?;
+    let key = as_key(key, pos)?;
+    let found = map.borrow().contains_key(&key);
+    Ok(Plain(LoxValue::Boolean(found)))
+}
+
+/// Removes the entry and returns its value
+pub fn delete(map: &LoxObj, key: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let map = as_map(map, pos)?;
+    let key = as_key(key, pos)?;
+    let removed = map.borrow_mut().remove(&key);
+    removed.ok_or(
+        runtime_err_at(pos)
+            .with_message(format!("Key {:?} is not in the map", key))
+            .build(),
+    )
+}
+
+pub fn map_from(entries: Vec<(LoxObj, LoxObj)>, pos: Span) -> LoxResult<LoxObj> {
+    let mut map = OrderedMap::new();
+    for (key, value) in entries {
+        map.insert(as_key(&key, pos)?, value);
+    }
+    Ok(LoxObj::map(map))
+}
+
+/// Iterator over a built-in iterable: items of a list, keys of a map or characters of a string.
+pub fn native_iter(iterable: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let iterator = match iterable {
+        LoxObj::Iterator(_) | LoxObj::Generator(_) => return Ok(iterable.clone()),
+        LoxObj::List(items) => NativeIterator::List {
+            items: items.clone(),
+            next: 0,
+        },
+        LoxObj::Map(map) => NativeIterator::Snapshot {
+            items: map.borrow().iter().map(|(key, _)| Plain(key.clone())).collect(),
+            next: 0,
+        },
+        Plain(val @ LoxValue::String(_)) => NativeIterator::Snapshot {
+            items: val
+                .to_string()
+                .chars()
+                .map(|c| Plain(LoxValue::String(format!("\"{}\"", c))))
+                .collect(),
+            next: 0,
+        },
+        _ => return runtime_err_at(pos).is_not(iterable.to_string(), "iterable").to_result(),
     };
+    Ok(LoxObj::iterator(iterator))
+}
 
-    let val_a = cast_to_int(a)?;
-    let val_b = cast_to_int(b)?;
+/// Iterator over integers from `start` up to, but not including, `end`
+pub fn range(start: &LoxObj, end: &LoxObj, step: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let step = cast_to_int(step, pos)?;
+    if step == 0 {
+        return runtime_err_at(pos)
+            .with_message("Range step cannot be 0".to_string())
+            .to_result();
+    }
 
-    Ok(Plain(Integer(val_a % val_b)))
+    Ok(LoxObj::iterator(NativeIterator::Range {
+        next: cast_to_int(start, pos)? as i32,
+        end: cast_to_int(end, pos)? as i32,
+        step: step as i32,
+    }))
 }
 
+/// Map from kinds of values to how many of them are live, plus their approximate size in
+/// kilobytes. Counts which don't fit in an integer are capped at `i16::MAX`, so heaps over about
+/// 32 MB all report the same size. Hosts get the exact size from `LoxInterpreter::heap_stats`.
+pub fn heap_stats(stats: &HeapStats) -> LoxObj {
+    let counts = [
+        ("kilobytes", stats.bytes / 1024),
+        ("strings", stats.strings),
+        ("lists", stats.lists),
+        ("maps", stats.maps),
+        ("objects", stats.objects),
+        ("functions", stats.functions),
+        ("classes", stats.classes),
+        ("iterators", stats.iterators),
+        ("generators", stats.generators),
+        ("host_objects", stats.host_objects),
+    ];
 
+    let mut map = OrderedMap::new();
+    for (kind, count) in counts {
+        let count = i16::try_from(count).unwrap_or(i16::MAX);
+        map.insert(LoxValue::String(format!("\"{}\"", kind)), Plain(Integer(count)));
+    }
+    LoxObj::map(map)
+}
+
+fn path_of(path: &LoxObj, pos: Span) -> LoxResult<PathBuf> {
+    cast_to_string(path, pos).map(PathBuf::from)
+}
+
+fn io_err(action: &str, err: io::Error, pos: Span) -> LoxError {
+    runtime_err_at(pos)
+        .with_message(format!("Could not {}: {}", action, err))
+        .build()
+}
+
+/// Contents of the file at `path` as a string
+pub fn read_file(path: &Path, pos: Span) -> LoxResult<LoxObj> {
+    let content = fs::read_to_string(path)
+        .map_err(|err| io_err(&format!("read {}", path.display()), err, pos))?;
+    Ok(Plain(LoxValue::String(format!("\"{}\"", content))))
+}
+
+/// Replaces the contents of the file at `path` with `text`
+pub fn write_file(path: &Path, text: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let text = cast_to_string(text, pos)?;
+    fs::write(path, text).map_err(|err| io_err(&format!("write {}", path.display()), err, pos))?;
+    Ok(Plain(LoxValue::Nil))
+}
+
+/// Value of the environment variable, or nil if it's not set
+pub fn env(name: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let name = cast_to_string(name, pos)?;
+    Ok(match std::env::var(name) {
+        Ok(value) => Plain(LoxValue::String(format!("\"{}\"", value))),
+        Err(_) => Plain(LoxValue::Nil),
+    })
+}
+
+/// Seconds since `start`. Integers are small, so this stops growing after about 9 hours.
+pub fn clock(start: Instant) -> LoxResult<LoxObj> {
+    let secs = i16::try_from(start.elapsed().as_secs()).unwrap_or(i16::MAX);
+    Ok(Plain(Integer(secs)))
+}
+
+/// Stops the program with an error carrying `code`, which the host turns into the exit status.
+pub fn exit(code: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    let code = cast_to_int(code, pos)?;
+    ErrBuilder::new()
+        .of_type(ErrType::Exit(code as i32))
+        .at(pos)
+        .with_message(format!("Exited with code {}", code))
+        .to_result()
+}
+
+/// Next line from stdin without the line break, or nil at the end of input
+pub fn input(pos: Span) -> LoxResult<LoxObj> {
+    let mut line = String::new();
+    let read = io::stdin()
+        .read_line(&mut line)
+        .map_err(|err| io_err("read from stdin", err, pos))?;
+    if read == 0 {
+        return Ok(Plain(LoxValue::Nil));
+    }
+
+    let line = line.trim_end_matches(&['\n', '\r'][..]);
+    Ok(Plain(LoxValue::String(format!("\"{}\"", line))))
+}
@@ -0,0 +1,68 @@
+//! Limits on how much work a program may do, so that untrusted scripts can't hang the host.
+//!
+//! The executor calls `tick` on every loop iteration and function call. Once a limit is exceeded,
+//! the program is aborted with an error telling which limit it was, see `ErrType::is_abort`.
+//! `try` doesn't catch those.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxResult};
+use crate::interpreter::execute::executing::Executor;
+
+#[derive(Default)]
+pub struct Limits {
+    /// Loop iterations and calls allowed in a single run.
+    pub max_steps: Option<u64>,
+    /// Wall-clock time allowed for a single run.
+    pub timeout: Option<Duration>,
+    /// Aborts the program once set, e.g. from another thread.
+    pub cancel: Option<Arc<AtomicBool>>,
+    steps: u64,
+    deadline: Option<Instant>,
+}
+
+impl Limits {
+    /// Resets the step count and the deadline. Called before each run.
+    pub fn start(&mut self) {
+        self.steps = 0;
+        self.deadline = self.timeout.map(|timeout| Instant::now() + timeout);
+    }
+
+    fn exceeded(&self) -> Option<(ErrType, String)> {
+        if let Some(cancel) = &self.cancel {
+            if cancel.load(Ordering::Relaxed) {
+                return Some((ErrType::Cancelled, "Execution was cancelled".to_string()));
+            }
+        }
+        if let Some(max_steps) = self.max_steps {
+            if self.steps > max_steps {
+                let msg = format!("Step budget of {} exceeded", max_steps);
+                return Some((ErrType::StepLimit, msg));
+            }
+        }
+        if let (Some(deadline), Some(timeout)) = (self.deadline, self.timeout) {
+            if Instant::now() >= deadline {
+                return Some((ErrType::Timeout, format!("Timeout of {:?} exceeded", timeout)));
+            }
+        }
+        None
+    }
+}
+
+impl Executor {
+    /// Counts one step of the program at `pos` and aborts it if any limit is exceeded.
+    pub(super) fn tick(&mut self, pos: Span) -> LoxResult<()> {
+        self.limits.steps += 1;
+        match self.limits.exceeded() {
+            Some((err_type, msg)) => ErrBuilder::new()
+                .of_type(err_type)
+                .at(pos)
+                .with_message(msg)
+                .to_result(),
+            None => Ok(()),
+        }
+    }
+}
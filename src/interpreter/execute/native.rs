@@ -0,0 +1,126 @@
+//! Functions implemented in Rust which can be called from Lox.
+//!
+//! Built-ins and functions registered by the host go through the same `Registry`. Registering a
+//! function binds it in the global scope, so scripts can shadow or pass it around like any other
+//! value.
+
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxError, LoxResult};
+use crate::interpreter::execute::capabilities::Capability;
+use crate::interpreter::execute::definitions::LoxObj;
+use crate::interpreter::execute::executing::Executor;
+use crate::interpreter::execute::heap::allocated_size;
+use crate::interpreter::execute::operations::eval_err;
+
+type NativeFn = dyn Fn(&mut Ctx, Vec<LoxObj>) -> LoxResult<LoxObj>;
+
+pub struct NativeFunction {
+    name: String,
+    arity: usize,
+    /// Capability which has to be granted before the function can be called.
+    capability: Option<Capability>,
+    fun: Box<NativeFn>,
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NativeFunction {}/{}", self.name, self.arity)
+    }
+}
+
+impl NativeFunction {
+    pub fn new<F>(name: &str, arity: usize, fun: F) -> Self
+    where
+        F: Fn(&mut Ctx, Vec<LoxObj>) -> LoxResult<LoxObj> + 'static,
+    {
+        NativeFunction {
+            name: name.to_string(),
+            arity,
+            capability: None,
+            fun: Box::new(fun),
+        }
+    }
+
+    pub fn requires(mut self, capability: Capability) -> Self {
+        self.capability = Some(capability);
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+/// What a native function can use of the executor calling it.
+pub struct Ctx<'a> {
+    pub(super) executor: &'a mut Executor,
+    /// Where the function was called.
+    pub pos: Span,
+}
+
+impl<'a> Ctx<'a> {
+    /// Runtime error at the call of the function.
+    pub fn error(&self, msg: String) -> LoxError {
+        ErrBuilder::new()
+            .of_type(ErrType::RuntimeError)
+            .at(self.pos)
+            .with_message(msg)
+            .build()
+    }
+
+    /// Calls a Lox function, e.g. a callback passed as an argument.
+    pub fn call(&mut self, func: &LoxObj, args: Vec<LoxObj>) -> LoxResult<LoxObj> {
+        self.executor.call(func, args, self.pos)
+    }
+}
+
+#[derive(Default)]
+pub struct Registry {
+    functions: HashMap<String, Rc<NativeFunction>>,
+}
+
+impl Registry {
+    pub fn get(&self, name: &str) -> Option<&Rc<NativeFunction>> {
+        self.functions.get(name)
+    }
+
+    pub fn names(&self) -> impl Iterator<Item = &String> {
+        self.functions.keys()
+    }
+}
+
+impl Executor {
+    /// Adds `function` to the registry and binds it in the global scope, replacing any function or
+    /// global of the same name.
+    pub fn register(&mut self, function: NativeFunction) {
+        let function = Rc::new(function);
+        self.state
+            .bind_global(function.name.clone(), LoxObj::Native(function.clone()));
+        self.natives.functions.insert(function.name.clone(), function);
+    }
+
+    pub(super) fn call_native(&mut self, function: &Rc<NativeFunction>, args: Vec<LoxObj>, pos: Span) -> LoxResult<LoxObj> {
+        if args.len() != function.arity {
+            return eval_err()
+                .at(pos)
+                .with_message(format!(
+                    "{} expects {} arguments, but got {}",
+                    function.name,
+                    function.arity,
+                    args.len()
+                ))
+                .to_result();
+        }
+        if let Some(capability) = function.capability {
+            self.capabilities.check(capability, pos)?;
+        }
+
+        let result = (function.fun)(&mut Ctx { executor: self, pos }, args)?;
+        self.allocate(allocated_size(&result), pos)?;
+        Ok(result)
+    }
+}
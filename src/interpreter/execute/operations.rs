@@ -1,7 +1,9 @@
-//! Logic related to unary and binary operators.
+//! Logic related to unary, binary and index operators.
 
 pub use binary_operations::*;
+pub use index_operations::*;
 pub use unary_operations::*;
 
 pub mod binary_operations;
+pub mod index_operations;
 pub mod unary_operations;
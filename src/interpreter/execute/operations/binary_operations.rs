@@ -1,30 +1,42 @@
 use crate::interpreter::execute::definitions::LoxObj;
-use crate::interpreter::errors::position::Position;
+use crate::interpreter::errors::span::Span;
 use crate::interpreter::errors::ErrType::LogicError;
 use crate::interpreter::errors::*;
 use crate::interpreter::tokens::*;
 
-pub fn eval_err() -> ErrBuilder {
+pub fn eval_err() -> ErrBuilder<ErrType> {
     ErrBuilder::new().of_type(LogicError)
 }
 
-pub fn handle(op: &Token, acc: LoxObj, val: LoxObj) -> LoxResult<LoxObj> {
+/// Applies the binary operator `op`. Errors point at `span`, which covers both operands.
+/// Lists, maps, instances and other objects are only equal to themselves.
+pub fn handle(op: &Token, acc: LoxObj, val: LoxObj, span: Span) -> LoxResult<LoxObj> {
+    let is_plain = |x: &LoxObj| matches!(x, LoxObj::Plain(_));
+    if !is_plain(&acc) || !is_plain(&val) {
+        match op.as_punct()? {
+            Punct::EqualEqual => return Ok(LoxObj::Plain(LoxValue::Boolean(acc.equals(&val)))),
+            Punct::BangEqual => return Ok(LoxObj::Plain(LoxValue::Boolean(!acc.equals(&val)))),
+            _ => {}
+        }
+    }
+
     let to_value = |x: LoxObj| {
-        let repr = format!("{:?}", x);
-        x.to_value().ok_or(eval_err().at(op.pos).is_not(repr, "value").build())
+        let repr = x.to_string();
+        x.to_value()
+            .ok_or_else(|| eval_err().at(span).with_message(format!("{} is not a value.", repr)).build())
     };
 
     let acc = to_value(acc)?;
     let val = to_value(val)?;
 
     let result = match op.as_punct()? {
-        Punct::Star => star(acc, val, op.pos),
-        Punct::Plus => plus(acc, val, op.pos),
-        Punct::Minus => minus(acc, val, op.pos),
-        Punct::EqualEqual => eq(acc, val, op.pos),
-        Punct::BangEqual => neq(acc, val, op.pos),
+        Punct::Star => star(acc, val, span),
+        Punct::Plus => plus(acc, val, span),
+        Punct::Minus => minus(acc, val, span),
+        Punct::EqualEqual => eq(acc, val, span),
+        Punct::BangEqual => neq(acc, val, span),
         _ => Err(eval_err()
-            .with_pos(op.pos)
+            .with_pos(op.span)
             .is_not(op, "a valid lox operation")
             .build()),
     }?;
@@ -32,59 +44,77 @@ pub fn handle(op: &Token, acc: LoxObj, val: LoxObj) -> LoxResult<LoxObj> {
     return Ok(LoxObj::Plain(result));
 }
 
-fn plus(acc: LoxValue, val: LoxValue, pos: Position) -> LoxResult<LoxValue> {
+fn overflow_err(op: &str, x: &i16, y: &i16, span: Span) -> LoxResult<LoxValue> {
+    eval_err()
+        .at(span)
+        .with_message(format!("Integer overflow in {} of {} and {}", op, x, y))
+        .to_result()
+}
+
+fn plus(acc: LoxValue, val: LoxValue, span: Span) -> LoxResult<LoxValue> {
     match (&acc, &val) {
-        (LoxValue::Integer(x), LoxValue::Integer(y)) => Ok(LoxValue::Integer(x + y)),
+        (LoxValue::Integer(x), LoxValue::Integer(y)) => match x.checked_add(*y) {
+            Some(result) => Ok(LoxValue::Integer(result)),
+            None => overflow_err("plus", x, y, span),
+        },
         (LoxValue::Boolean(x), LoxValue::Boolean(y)) => Ok(LoxValue::Boolean(*x || *y)),
         _ => eval_err()
             .cant_perform_a_on_b_and_c("plus", acc, val)
-            .with_pos(pos)
+            .with_pos(span)
             .to_result(),
     }
 }
 
-fn star(acc: LoxValue, val: LoxValue, pos: Position) -> LoxResult<LoxValue> {
+fn star(acc: LoxValue, val: LoxValue, span: Span) -> LoxResult<LoxValue> {
     match (&acc, &val) {
-        (LoxValue::Integer(x), LoxValue::Integer(y)) => Ok(LoxValue::Integer(x * y)),
+        (LoxValue::Integer(x), LoxValue::Integer(y)) => match x.checked_mul(*y) {
+            Some(result) => Ok(LoxValue::Integer(result)),
+            None => overflow_err("star", x, y, span),
+        },
         (LoxValue::Boolean(x), LoxValue::Boolean(y)) => Ok(LoxValue::Boolean(*x && *y)),
         _ => eval_err()
             .cant_perform_a_on_b_and_c("star", acc, val)
-            .with_pos(pos)
+            .with_pos(span)
             .to_result(),
     }
 }
 
-fn minus(acc: LoxValue, val: LoxValue, pos: Position) -> LoxResult<LoxValue> {
+fn minus(acc: LoxValue, val: LoxValue, span: Span) -> LoxResult<LoxValue> {
     match (&acc, &val) {
-        (LoxValue::Integer(x), LoxValue::Integer(y)) => Ok(LoxValue::Integer(x - y)),
+        (LoxValue::Integer(x), LoxValue::Integer(y)) => match x.checked_sub(*y) {
+            Some(result) => Ok(LoxValue::Integer(result)),
+            None => overflow_err("minus", x, y, span),
+        },
         (LoxValue::Boolean(x), LoxValue::Boolean(y)) => Ok(LoxValue::Boolean(*x && !*y)),
         _ => eval_err()
             .cant_perform_a_on_b_and_c("minus", acc, val)
-            .with_pos(pos)
+            .with_pos(span)
             .to_result(),
     }
 }
 
-fn eq(acc: LoxValue, val: LoxValue, pos: Position) -> LoxResult<LoxValue> {
+fn eq(acc: LoxValue, val: LoxValue, span: Span) -> LoxResult<LoxValue> {
     match (&acc, &val) {
         (LoxValue::Integer(x), LoxValue::Integer(y)) => Ok(LoxValue::Boolean(x == y)),
         (LoxValue::Boolean(x), LoxValue::Boolean(y)) => Ok(LoxValue::Boolean(x == y)),
         (LoxValue::String(x), LoxValue::String(y)) => Ok(LoxValue::Boolean(x == y)),
+        (LoxValue::Nil, _) | (_, LoxValue::Nil) => Ok(LoxValue::Boolean(acc == val)),
         _ => eval_err()
             .cant_perform_a_on_b_and_c("equality check", acc, val)
-            .with_pos(pos)
+            .with_pos(span)
             .to_result(),
     }
 }
 
-fn neq(acc: LoxValue, val: LoxValue, pos: Position) -> LoxResult<LoxValue> {
+fn neq(acc: LoxValue, val: LoxValue, span: Span) -> LoxResult<LoxValue> {
     match (&acc, &val) {
         (LoxValue::Integer(x), LoxValue::Integer(y)) => Ok(LoxValue::Boolean(x != y)),
         (LoxValue::Boolean(x), LoxValue::Boolean(y)) => Ok(LoxValue::Boolean(x != y)),
         (LoxValue::String(x), LoxValue::String(y)) => Ok(LoxValue::Boolean(x != y)),
+        (LoxValue::Nil, _) | (_, LoxValue::Nil) => Ok(LoxValue::Boolean(acc != val)),
         _ => eval_err()
             .cant_perform_a_on_b_and_c("inequality check", acc, val)
-            .with_pos(pos)
+            .with_pos(span)
             .to_result(),
     }
 }
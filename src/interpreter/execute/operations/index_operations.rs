@@ -0,0 +1,93 @@
+use crate::interpreter::errors::{span::Span, ErrBuilder, ErrType, ErrType::RuntimeError, LoxResult};
+use crate::interpreter::execute::definitions::{LoxList, LoxMap, LoxObj, LoxObj::Plain};
+use crate::interpreter::tokens::LoxValue::{self, Integer};
+
+fn index_err(pos: Span) -> ErrBuilder<ErrType, Span> {
+    ErrBuilder::new()
+        .of_type(RuntimeError)
+        .at(pos)
+        .while_("indexing")
+}
+
+/// Maps negative indexes to positions counted from the end. The result may still be out of range.
+pub fn normalize_index(index: i16, len: usize) -> i64 {
+    match index {
+        i if i < 0 => len as i64 + i as i64,
+        i => i as i64,
+    }
+}
+
+pub fn as_list(obj: &LoxObj, pos: Span) -> LoxResult<LoxList> {
+    match obj {
+        LoxObj::List(items) => Ok(items.clone()),
+        _ => index_err(pos).is_not(obj.to_string(), "a list").to_result(),
+    }
+}
+
+pub fn as_map(obj: &LoxObj, pos: Span) -> LoxResult<LoxMap> {
+    match obj {
+        LoxObj::Map(map) => Ok(map.clone()),
+        _ => index_err(pos).is_not(obj.to_string(), "a map").to_result(),
+    }
+}
+
+/// Only plain values can be used as map keys.
+pub fn as_key(obj: &LoxObj, pos: Span) -> LoxResult<LoxValue> {
+    match obj {
+        Plain(val) => Ok(val.clone()),
+        _ => index_err(pos).is_not(obj.to_string(), "a valid map key").to_result(),
+    }
+}
+
+/// Resolves `index` to a valid position in `items`.
+fn position_in(items: &[LoxObj], index: &LoxObj, pos: Span) -> LoxResult<usize> {
+    let index = match index {
+        Plain(Integer(i)) => *i,
+        _ => return index_err(pos).is_not(index.to_string(), "integer").to_result(),
+    };
+
+    let normalized = normalize_index(index, items.len());
+    if normalized < 0 || normalized >= items.len() as i64 {
+        return index_err(pos)
+            .with_message(format!(
+                "Index {} is out of range for list of length {}",
+                index,
+                items.len()
+            ))
+            .to_result();
+    }
+
+    Ok(normalized as usize)
+}
+
+/// Evaluates `target[index]`
+pub fn get_index(target: &LoxObj, index: &LoxObj, pos: Span) -> LoxResult<LoxObj> {
+    if let LoxObj::Map(map) = target {
+        let key = as_key(index, pos)?;
+        return map.borrow().get(&key).cloned().ok_or(
+            index_err(pos)
+                .with_message(format!("Key {:?} is not in the map", key))
+                .build(),
+        );
+    }
+
+    let list = as_list(target, pos)?;
+    let items = list.borrow();
+    let i = position_in(&items, index, pos)?;
+    Ok(items[i].clone())
+}
+
+/// Evaluates `target[index] = value`. Assigning to a missing map key inserts it.
+pub fn set_index(target: &LoxObj, index: &LoxObj, value: LoxObj, pos: Span) -> LoxResult<()> {
+    if let LoxObj::Map(map) = target {
+        let key = as_key(index, pos)?;
+        map.borrow_mut().insert(key, value);
+        return Ok(());
+    }
+
+    let list = as_list(target, pos)?;
+    let mut items = list.borrow_mut();
+    let i = position_in(&items, index, pos)?;
+    items[i] = value;
+    Ok(())
+}
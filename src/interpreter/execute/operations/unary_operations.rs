@@ -1,18 +1,24 @@
 use crate::interpreter::execute::operations::unary_operations::LoxObj::Plain;
-use crate::interpreter::errors::{position::Position, ErrBuilder, ErrType::LogicError, LoxResult};
+use crate::interpreter::errors::{span::Span, ErrBuilder, ErrType, ErrType::LogicError, LoxResult};
 use crate::interpreter::execute::definitions::LoxObj;
 use crate::interpreter::tokens::{LoxValue::Boolean, LoxValue::Integer, Punct, Token};
 
-fn unary_op_err() -> ErrBuilder {
+fn unary_op_err() -> ErrBuilder<ErrType> {
     ErrBuilder::new()
         .of_type(LogicError)
         .while_("evaluating unary expression")
 }
 
-pub fn negate(raw: &LoxObj, at: Position) -> LoxResult<LoxObj> {
+pub fn negate(raw: &LoxObj, at: Span) -> LoxResult<LoxObj> {
     match raw {
         Plain(Boolean(b)) => Ok(Plain(Boolean(!b))),
-        Plain(Integer(b)) => Ok(Plain(Integer(-b))),
+        Plain(Integer(b)) => match b.checked_neg() {
+            Some(negated) => Ok(Plain(Integer(negated))),
+            None => unary_op_err()
+                .with_pos(at)
+                .with_message(format!("Integer overflow while negating {}", b))
+                .to_result(),
+        },
         _ => unary_op_err()
             .with_pos(at)
             .with_message(format!("Cannot negate {:?}", raw.to_string()))
@@ -22,10 +28,10 @@ pub fn negate(raw: &LoxObj, at: Position) -> LoxResult<LoxObj> {
 
 /// Applies `op` to `right`
 pub fn unary_op(op: &Token, right: &LoxObj) -> LoxResult<LoxObj> {
-    let pos = op.pos;
+    let pos = op.span;
     let op = op.as_punct()?;
     match op {
-        Minus => negate(right, pos),
+        Punct::Minus => negate(right, pos),
         _ => unary_op_err()
             .with_pos(pos)
             .with_message(format!(
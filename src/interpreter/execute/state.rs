@@ -9,7 +9,7 @@ use std::hash::Hasher;
 use crate::interpreter::tokens::Token;
 use crate::interpreter::tokens::TokenValue;
 use crate::interpreter::errors::position::Position;
-use crate::interpreter::errors::ErrBuilder;
+use crate::interpreter::errors::{ErrBuilder, ErrType};
 use crate::interpreter::errors::ErrType::LogicError;
 use crate::interpreter::errors::LoxResult;
 use crate::interpreter::execute::definitions::{LoxObj};
@@ -32,41 +32,43 @@ pub struct Scope {
 
 impl Scope {
     pub fn new(name: String) -> Self {
-
-        let mut bindings = HashMap::with_capacity(100);
-        bindings.insert("mod".to_string(), LoxObj::Inbuilt("mod".to_string()));
-
         Scope {
-            bindings,
+            bindings: HashMap::with_capacity(100),
             name,
         }
     }
 
-    pub fn bind(&mut self, identifier: Token, obj: LoxObj) {
-        let name = match identifier.val {
-            TokenValue::Id(name) => name,
-            _  => panic!("{:?} is not an identfier!", identifier) 
-        };
-        self.bindings.insert(name, obj);
+    pub fn bind(&mut self, identifier: Token, obj: LoxObj) -> LoxResult<()> {
+        let name = identifier.as_identifier()?;
+        self.bindings.insert(name.clone(), obj);
+        Ok(())
     }
 
     pub fn get(&self, identifier: &Token) -> LoxResult<LoxObj> {
-        let Token { val, pos } = identifier;
-        let name = match val {
-            TokenValue::Id(name) => name,
-            _  => panic!("{:?} is not an identfier!", identifier) 
-        };
+        let name = identifier.as_identifier()?;
 
         let obj = self.bindings.get(name).ok_or(self.err()
-            .with_pos(*pos)
-            .with_message(format!("Variable {:?} is not in scope", identifier))
+            .at(identifier.span)
+            .with_message(format!("Variable {} is not in scope", name))
             .build())?
             .clone();
 
         return Ok( obj )
     }
 
-    fn err(&self) -> ErrBuilder {
+    pub fn values(&self) -> impl Iterator<Item = &LoxObj> {
+        self.bindings.values()
+    }
+
+    /// Like `get`, but without building an error, which matters when searching many scopes.
+    fn contains(&self, identifier: &Token) -> bool {
+        match &identifier.val {
+            TokenValue::Id(name) => self.bindings.contains_key(name),
+            _ => false,
+        }
+    }
+
+    fn err(&self) -> ErrBuilder<ErrType> {
         ErrBuilder::new().of_type(LogicError)
     }
 }
@@ -101,37 +103,66 @@ impl State {
         self.scope_stack.pop().map(|_| ())
     }
 
-    pub fn bind(&mut self, identifier: Token, obj: LoxObj) {
+    /// Number of scopes on the stack, including the global one.
+    pub fn depth(&self) -> usize {
+        self.scope_stack.len()
+    }
+
+    /// Removes and returns all scopes above `depth`, so they can be restored later.
+    pub fn split_off_scopes(&mut self, depth: usize) -> Vec<Scope> {
+        self.scope_stack.split_off(depth)
+    }
+
+    pub fn restore_scopes(&mut self, scopes: Vec<Scope>) {
+        self.scope_stack.extend(scopes)
+    }
+
+    pub fn bind(&mut self, identifier: Token, obj: LoxObj) -> LoxResult<()> {
         let relevant_scope = self
             .scope_stack
             .iter_mut()
-            .filter(|scope| scope.get(&identifier).is_ok())
+            .filter(|scope| scope.contains(&identifier))
             .next(); // first scope which contains this identifier
 
         return match relevant_scope {
-            Some(scope) => {
-                scope.bind(identifier, LoxObj::from(obj));
-            }
-            None => {
-                self.get_curr_scope()
-                    .bind(identifier, LoxObj::from(obj));
-            }
+            Some(scope) => scope.bind(identifier, LoxObj::from(obj)),
+            None => self.get_curr_scope().bind(identifier, LoxObj::from(obj)),
         };
     }
 
+    /// Values bound in any of the scopes.
+    pub fn values(&self) -> impl Iterator<Item = &LoxObj> {
+        self.scope_stack.iter().flat_map(|scope| scope.values())
+    }
+
+    pub fn get_global(&self, name: &str) -> Option<LoxObj> {
+        self.scope_stack[0].bindings.get(name).cloned()
+    }
+
+    /// Binds `name` in the global scope.
+    pub fn bind_global(&mut self, name: String, obj: LoxObj) {
+        self.scope_stack[0].bindings.insert(name, obj);
+    }
+
+    /// Binds `identifier` in the innermost scope, shadowing any outer binding.
+    pub fn declare(&mut self, identifier: Token, obj: LoxObj) -> LoxResult<()> {
+        self.get_curr_scope().bind(identifier, obj)
+    }
+
     pub fn get(&self, identifier: &Token) -> LoxResult<LoxObj> {
+        let name = identifier.as_identifier()?;
         for scope in self.scope_stack.iter().rev() {
-            if let Ok(obj) = scope.get(identifier) {
-                return Ok(obj);
+            if scope.contains(identifier) {
+                return scope.get(identifier);
             }
         }
         self.err()
-            .at(identifier.pos)
-            .with_message(format!("Variable {:?} is not in scope", identifier))
+            .at(identifier.span)
+            .with_message(format!("Variable {} is not in scope", name))
             .to_result()
     }
 
-    fn err(&self) -> ErrBuilder {
+    fn err(&self) -> ErrBuilder<ErrType> {
         ErrBuilder::new().of_type(RuntimeError)
     }
 }
@@ -1,8 +1,9 @@
 //! The parser. Basically a pure function from a `Vec<Token>` to a `Vec<Statement>`.
 
 use crate::interpreter::errors::position::Position;
+use crate::interpreter::errors::span::Span;
 use crate::interpreter::{
-    errors::{ErrBuilder, ErrType::ParsingErr, LoxResult},
+    errors::{ErrBuilder, ErrType, ErrType::ParsingErr, LoxResult},
     readers::{Reader, TokenReader},
     scanner::ScannerOutput,
     tokens::Punct::*,
@@ -10,15 +11,38 @@ use crate::interpreter::{
     LoxError,
 };
 
-pub mod locator;
 pub mod pretty_printing;
 pub mod structure;
 pub mod visitor;
 
+use std::cell::RefCell;
 use structure::*;
 
 pub struct Parser {
     token_reader: TokenReader,
+    /// Labels of the loops enclosing the statement being parsed, innermost last.
+    loop_labels: RefCell<Vec<Option<String>>>,
+    /// For each function enclosing the statement being parsed, whether a `yield` was found in it.
+    generator_flags: RefCell<Vec<bool>>,
+    /// Errors of the statements which were skipped so far.
+    errors: RefCell<Vec<LoxError>>,
+}
+
+/// The statements which could be parsed, and the errors of those which couldn't, ordered by
+/// their position.
+pub struct ParserOutput {
+    pub program: Program,
+    pub errors: Vec<LoxError>,
+}
+
+impl ParserOutput {
+    /// The program, if there were no errors, or the first of the errors.
+    pub fn into_result(mut self) -> LoxResult<Program> {
+        match self.errors.is_empty() {
+            true => Ok(self.program),
+            false => Err(self.errors.remove(0)),
+        }
+    }
 }
 
 /// Parser á la recursive descent.
@@ -32,29 +56,85 @@ impl Parser {
     pub fn new(scanner_output: ScannerOutput) -> Self {
         Parser {
             token_reader: TokenReader::from_vec(scanner_output.tokens),
+            loop_labels: RefCell::new(Vec::new()),
+            generator_flags: RefCell::new(Vec::new()),
+            errors: RefCell::new(Vec::new()),
         }
     }
 
-    pub fn parse(&self) -> LoxResult<Program> {
-        self.program()
+    /// Parses as much of the program as possible. Statements with errors are left out of it.
+    pub fn parse(&self) -> ParserOutput {
+        let program = self.program();
+        let mut errors = self.errors.take();
+        errors.sort_by_key(|err| (err.pos().line, err.pos().line_pos));
+        ParserOutput { program, errors }
     }
 
-    fn program(&self) -> LoxResult<Program> {
+    fn program(&self) -> Program {
         let mut stmts = Vec::new();
         while !self.is_finished() {
-            let next_stmt = self.statement()?;
-            stmts.push(next_stmt);
-            self.consume_punct(Semicolon, "Reading statements")?;
+            if let Some(next_stmt) = self.recoverable_statement() {
+                stmts.push(next_stmt);
+            }
         }
 
-        Ok(stmts)
+        stmts
     }
 
     fn is_finished(&self) -> bool {
         self.token_reader
             .peek()
             .map(|t: &Token| t.equals(Eof))
-            .unwrap_or(false)
+            .unwrap_or(true)
+    }
+
+    /// Parses a statement with its `;`. On error, the error is recorded and the parser skips to
+    /// where the next statement probably starts.
+    fn recoverable_statement(&self) -> Option<Stmt> {
+        let start = self.token_reader.pos();
+        let stmt = self.statement().and_then(|stmt| {
+            self.end_of_statement(&stmt)?;
+            Ok(stmt)
+        });
+
+        match stmt {
+            Ok(stmt) => Some(stmt),
+            Err(err) => {
+                // the scanner already reported what it couldn't read
+                if !self.at_error_token() {
+                    self.errors.borrow_mut().push(err);
+                }
+                self.synchronize(start);
+                None
+            }
+        }
+    }
+
+    /// Whether the parser stopped at a token the scanner couldn't read.
+    fn at_error_token(&self) -> bool {
+        let is_error = |token: Option<&Token>| token.is_some_and(|t| matches!(t.val, TokenValue::Error(_)));
+        is_error(self.token_reader.peek()) || is_error(self.token_reader.previous())
+    }
+
+    /// Skips tokens until after a `;` or before a token which starts a statement or ends a block.
+    /// Always skips at least one token if the failed statement started at `start` and consumed
+    /// nothing, so that parsing makes progress.
+    fn synchronize(&self, start: usize) {
+        if self.token_reader.pos() == start && !self.is_finished() {
+            self.token_reader.advance();
+        }
+
+        while let Some(token) = self.token_reader.peek() {
+            let after_semicolon = self
+                .token_reader
+                .previous()
+                .map(|t| t.equals(Semicolon))
+                .unwrap_or(false);
+            if after_semicolon || token.equals(Eof) || token.equals(RightBrace) || starts_statement(token) {
+                return;
+            }
+            self.token_reader.advance();
+        }
     }
 
     fn scoped_program(&self) -> LoxResult<Program> {
@@ -68,29 +148,80 @@ impl Parser {
         self.consume_punct(LeftBrace, info)?;
 
         let mut program = Vec::new();
-        while !end_of_scope() {
-            let next_stmt = self.statement()?;
-            program.push(next_stmt);
-            self.consume_punct(Semicolon, "Reading statements")?;
+        while !end_of_scope() && !self.is_finished() {
+            if let Some(next_stmt) = self.recoverable_statement() {
+                program.push(next_stmt);
+            }
         }
 
         self.consume_punct(RightBrace, info)?;
         Ok(program)
     }
 
-    fn statement(&self) -> LoxResult<Statement> {
+    /// Consumes the `;` which ends `stmt`. For statements ending with a block it is optional.
+    fn end_of_statement(&self, stmt: &Stmt) -> LoxResult<()> {
+        let semicolon = self.token_reader.advance_if(|t| t.equals(Semicolon));
+        if semicolon.is_some() || stmt.statement.is_self_terminating() {
+            return Ok(());
+        }
+
+        let found = self
+            .token_reader
+            .peek()
+            .map(|t| format!("{:?}", t.val))
+            .unwrap_or("nothing".to_string());
+
+        let err = match self.token_reader.previous() {
+            Some(last_token) => ErrBuilder::new().at(last_token.span.at_end()).of_type(ParsingErr),
+            None => self.parsing_err(),
+        };
+        err
+            .with_message(format!("Expected {:?} after statement, but found {}.", Semicolon, found))
+            .to_result()
+    }
+
+    fn statement(&self) -> LoxResult<Stmt> {
         let stmt_kind = self.statement_decider()?;
+        let start = self.next_span();
         let stmt = match stmt_kind {
             StatementKind::Expr => self.expr_stmt(),
             StatementKind::Fun => self.function_definition(),
             StatementKind::Let => self.var_stmt(),
             StatementKind::If => self.if_stmt(),
+            StatementKind::Block => self.block_stmt(),
             StatementKind::Return => self.return_(),
-            StatementKind::WhileLoop => self.while_stmt(),
+            StatementKind::Yield => self.yield_stmt(),
+            StatementKind::Throw => self.throw_stmt(),
+            StatementKind::Try => self.try_stmt(),
+            StatementKind::WhileLoop => self.while_stmt(None),
+            StatementKind::ForIn => self.for_in_stmt(None),
             StatementKind::Print => self.print_stmt(),
             StatementKind::Class => self.class_def_stmt(),
+            StatementKind::Labeled => self.labeled_stmt(),
+            StatementKind::Break => self.break_stmt(),
+            StatementKind::Continue => self.continue_stmt(),
         };
-        Ok(stmt?)
+        Ok(Stmt {
+            statement: stmt?,
+            span: self.span_from(start),
+        })
+    }
+
+    /// Span of the next token, or of the last one at the end.
+    fn next_span(&self) -> Span {
+        self.token_reader
+            .peek()
+            .or(self.token_reader.previous())
+            .map(|t| t.span)
+            .unwrap_or_default()
+    }
+
+    /// Span from `start` to the end of the last consumed token.
+    fn span_from(&self, start: Span) -> Span {
+        match self.token_reader.previous() {
+            Some(last) => start.to(last.span),
+            None => start,
+        }
     }
 
     fn statement_decider(&self) -> LoxResult<StatementKind> {
@@ -107,16 +238,55 @@ impl Parser {
                 Kwd::If => Ok(StatementKind::If),
                 Kwd::Var => Ok(StatementKind::Let),
                 Kwd::While => Ok(StatementKind::WhileLoop),
+                Kwd::For => Ok(StatementKind::ForIn),
                 Kwd::Fun => Ok(StatementKind::Fun),
                 Kwd::Return => Ok(StatementKind::Return),
+                Kwd::Yield => Ok(StatementKind::Yield),
+                Kwd::Throw => Ok(StatementKind::Throw),
+                Kwd::Try => Ok(StatementKind::Try),
                 Kwd::Class => Ok(StatementKind::Class),
+                Kwd::Break => Ok(StatementKind::Break),
+                Kwd::Continue => Ok(StatementKind::Continue),
                 _ => Ok(StatementKind::Expr),
             };
         }
 
+        if first_token.equals(LeftBrace) && !self.map_literal_decider() {
+            return Ok(StatementKind::Block);
+        }
+
+        let is_label = first_token.is_identifier()
+            && self
+                .token_reader
+                .peek_n(1)
+                .map(|t| t.equals(Colon))
+                .unwrap_or(false);
+        if is_label {
+            return Ok(StatementKind::Labeled);
+        }
+
         return Ok(StatementKind::Expr);
     }
 
+    /// Tells a map literal from a block, assuming the next token is `{`. A map literal starts with
+    /// `{ key :`, while a block can only have a `:` there if it starts with a labeled loop.
+    fn map_literal_decider(&self) -> bool {
+        let token_after = |n: usize| self.token_reader.peek_n(n);
+        let (Some(key), Some(colon)) = (token_after(1), token_after(2)) else {
+            return false;
+        };
+
+        if !colon.equals(Colon) {
+            return false;
+        }
+
+        let is_labeled_loop = key.is_identifier()
+            && token_after(3)
+                .map(|t| t.equals(Kwd::While) || t.equals(Kwd::For))
+                .unwrap_or(false);
+        !is_labeled_loop
+    }
+
     fn class_def_stmt(&self) -> LoxResult<Statement> {
         let info = "parsing class def";
         let mut fields = Vec::new();
@@ -129,7 +299,7 @@ impl Parser {
         if !class_name.is_identifier() {
             return self
                 .parsing_err()
-                .expected_but_found("identifier", class_name)
+                .expected_but_found("identifier", &class_name.val)
                 .to_result();
         }
 
@@ -140,14 +310,15 @@ impl Parser {
             }
 
             let next_stmt = self.statement()?;
-            self.consume_punct(Punct::Semicolon, info)?;
-            match next_stmt {
+            self.end_of_statement(&next_stmt)?;
+            match next_stmt.statement {
                 Statement::Let(lval, rval) => fields.push((lval, rval)),
                 Statement::Fun(_, fn_def) => methods.push(fn_def),
-                _ => {
-                    return self
-                        .parsing_err()
-                        .expected_but_found("function or let statement", next_stmt)
+                other => {
+                    return ErrBuilder::new()
+                        .at(next_stmt.span)
+                        .of_type(ParsingErr)
+                        .expected_but_found("function or let statement", other)
                         .to_result()
                 }
             }
@@ -168,25 +339,109 @@ impl Parser {
         Ok(Statement::Return(expr))
     }
 
+    fn throw_stmt(&self) -> LoxResult<Statement> {
+        let info = "Parsing a throw statement";
+        let pos = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?
+            .span;
+        self.consume_kwd(Kwd::Throw, info)?;
+        Ok(Statement::Throw(pos, self.expression()?))
+    }
+
+    fn try_stmt(&self) -> LoxResult<Statement> {
+        let info = "Parsing a try statement";
+        self.consume_kwd(Kwd::Try, info)?;
+        let body = self.scoped_program()?;
+
+        let mut catch = None;
+        if self.token_reader.advance_if(|t| t.equals(Kwd::Catch)).is_some() {
+            self.consume_punct(LeftParen, info)?;
+            let error = self
+                .token_reader
+                .advance_or(self.expected_next_token_err(info))?;
+            if !error.is_identifier() {
+                return self
+                    .parsing_err()
+                    .expected_but_found("identifier", &error.val)
+                    .while_(info)
+                    .to_result();
+            }
+            let error = error.clone();
+            self.consume_punct(RightParen, info)?;
+            catch = Some((error, self.scoped_program()?));
+        }
+
+        let mut finally = None;
+        if self.token_reader.advance_if(|t| t.equals(Kwd::Finally)).is_some() {
+            finally = Some(self.scoped_program()?);
+        }
+
+        if catch.is_none() && finally.is_none() {
+            let found = self.token_reader.peek().map(|t| format!("{:?}", t.val));
+            return self
+                .parsing_err()
+                .expected_but_found("catch or finally", found)
+                .while_(info)
+                .to_result();
+        }
+
+        Ok(Statement::Try(TryStatement {
+            body,
+            catch,
+            finally,
+        }))
+    }
+
+    fn yield_stmt(&self) -> LoxResult<Statement> {
+        let info = "Parsing a yield statement";
+        let pos = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?
+            .span;
+        self.consume_kwd(Kwd::Yield, info)?;
+
+        match self.generator_flags.borrow_mut().last_mut() {
+            Some(is_generator) => *is_generator = true,
+            None => {
+                return ErrBuilder::new()
+                    .at(pos)
+                    .of_type(ParsingErr)
+                    .with_message("Yield outside of a function".to_string())
+                    .to_result()
+            }
+        }
+
+        Ok(Statement::Yield(pos, self.expression()?))
+    }
+
     fn function_definition(&self) -> LoxResult<Statement> {
-        let pos = self.token_reader.peek().map(|t| t.pos);
+        let pos = self.token_reader.peek().map(|t| t.span);
         self.consume_kwd(Kwd::Fun, "Parsing function definition")?;
 
         let fn_name = self.token_reader.advance_or(self.expected_next_token_err("Parsing function definition"))?;
         if !fn_name.is_identifier() {
-            return self.parsing_err().is_not(fn_name, "identifier").to_result()
+            return self.parsing_err().is_not(&fn_name.val, "identifier").to_result()
         }
 
         let args = self.fn_def_args()?;
-        let fn_body = self.scoped_program()?;
+
+        // loops enclosing the definition are not reachable from inside the function body
+        let enclosing_loops = self.loop_labels.replace(Vec::new());
+        self.generator_flags.borrow_mut().push(false);
+        let fn_body = self.scoped_program();
+        let is_generator = self.generator_flags.borrow_mut().pop().unwrap_or(false);
+        self.loop_labels.replace(enclosing_loops);
+        let fn_body = fn_body?;
 
         let fn_def = FunctionDefinition {
             name: fn_name.clone(),
             args: args,
             body: fn_body,
+            is_generator,
         };
 
-        Ok(Statement::Fun(pos.unwrap(), fn_def))
+        Ok(Statement::Fun(pos.unwrap_or(fn_name.span), fn_def))
     }
 
     fn fn_def_args(&self) -> LoxResult<Vec<Token>> {
@@ -219,7 +474,7 @@ impl Parser {
 
             let next_token = self.token_reader.advance_or(self.expected_next_token_err(info))?;
             if !next_token.is_identifier() {
-                return self.parsing_err().expected_but_found("identifier", next_token).to_result();
+                return self.parsing_err().expected_but_found("identifier", &next_token.val).to_result();
             }
 
             args.push(next_token.clone());
@@ -229,26 +484,156 @@ impl Parser {
         Ok(args)
     }
 
-    fn while_stmt(&self) -> LoxResult<Statement> {
-        self.consume_kwd(Kwd::While, "")
-            .unwrap_or_else(|_err| panic!("Statement decider did something wrong. Attempted to parse a 'while statement' but no `while` keyword found"));
+    fn labeled_stmt(&self) -> LoxResult<Statement> {
+        let info = "parsing labeled loop";
+        let label = self
+            .token_reader
+            .advance_or(self.expected_next_token_err(info))?
+            .clone();
+        self.consume_punct(Colon, info)?;
+
+        let next_token = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?;
+        if next_token.equals(Kwd::While) {
+            return self.while_stmt(Some(label));
+        }
+        if next_token.equals(Kwd::For) {
+            return self.for_in_stmt(Some(label));
+        }
+
+        self.parsing_err()
+            .expected_but_found("loop after label", &next_token.val)
+            .while_(info)
+            .to_result()
+    }
+
+    fn while_stmt(&self, label: Option<Token>) -> LoxResult<Statement> {
+        self.consume_kwd(Kwd::While, "parsing while statement")?;
         let cond = self.parenthesized_expr()?;
-        let prog = self.scoped_program()?;
-        Ok(Statement::WhileLoop(cond, prog))
+        let prog = self.loop_body(&label)?;
+        Ok(Statement::WhileLoop(label, cond, prog))
+    }
+
+    fn for_in_stmt(&self, label: Option<Token>) -> LoxResult<Statement> {
+        let info = "parsing for loop";
+        self.consume_kwd(Kwd::For, info)?;
+        self.consume_punct(LeftParen, info)?;
+
+        let variable = self
+            .token_reader
+            .advance_or(self.expected_next_token_err(info))?;
+        if !variable.is_identifier() {
+            return self
+                .parsing_err()
+                .expected_but_found("identifier", &variable.val)
+                .while_(info)
+                .to_result();
+        }
+
+        self.consume_kwd(Kwd::In, info)?;
+        let iterable = self.expression()?;
+        self.consume_punct(RightParen, info)?;
+
+        let prog = self.loop_body(&label)?;
+        Ok(Statement::ForIn(label, variable.clone(), iterable, prog))
+    }
+
+    /// Parses a loop body, making `break` and `continue` legal inside of it.
+    fn loop_body(&self, label: &Option<Token>) -> LoxResult<Program> {
+        let label_name = label.as_ref().and_then(|t| t.identifier()).cloned();
+        self.loop_labels.borrow_mut().push(label_name);
+        let body = self.scoped_program();
+        self.loop_labels.borrow_mut().pop();
+        body
+    }
+
+    fn break_stmt(&self) -> LoxResult<Statement> {
+        let (pos, label) = self.loop_jump(Kwd::Break, "Parsing a break statement")?;
+        Ok(Statement::Break(pos, label))
+    }
+
+    fn continue_stmt(&self) -> LoxResult<Statement> {
+        let (pos, label) = self.loop_jump(Kwd::Continue, "Parsing a continue statement")?;
+        Ok(Statement::Continue(pos, label))
+    }
+
+    /// Parses `kwd [label]` and checks that it refers to an enclosing loop.
+    fn loop_jump(&self, kwd: Kwd, info: &str) -> LoxResult<(Span, Option<Token>)> {
+        let pos = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?
+            .span;
+        self.consume_kwd(kwd.clone(), info)?;
+        let label = self.token_reader.advance_if(Token::is_identifier).cloned();
+
+        let loop_labels = self.loop_labels.borrow();
+        if loop_labels.is_empty() {
+            return ErrBuilder::new()
+                .at(pos)
+                .of_type(ParsingErr)
+                .with_message(format!("{:?} outside of a loop", kwd))
+                .to_result();
+        }
+
+        if let Some(name) = label.as_ref().and_then(|t| t.identifier()) {
+            let is_enclosing = loop_labels.iter().any(|l| l.as_ref() == Some(name));
+            if !is_enclosing {
+                return ErrBuilder::new()
+                    .at(pos)
+                    .of_type(ParsingErr)
+                    .with_message(format!("No enclosing loop labeled {:?}", name))
+                    .to_result();
+            }
+        }
+
+        Ok((pos, label))
     }
 
     fn expr_stmt(&self) -> LoxResult<Statement> {
         let expr = self.expression()?;
+        if self.token_reader.advance_if(|t| t.equals(Equal)).is_some() {
+            return self.assignment_stmt(expr);
+        }
         Ok(Statement::Expr(expr))
     }
 
+    /// Turns already parsed `expr` into the target of `expr = value`. Only `xs[i] = value` and
+    /// `obj.name = value` are allowed.
+    fn assignment_stmt(&self, expr: Expr) -> LoxResult<Statement> {
+        match expr.as_unary() {
+            Some(Unary::Index(None, target, span, index)) => {
+                let index_target = IndexTarget {
+                    target: target.as_ref().clone(),
+                    span: *span,
+                    index: index.as_ref().clone(),
+                };
+                let value = self.expression()?;
+                Ok(Statement::SetIndex(index_target, RVal { expr: value }))
+            }
+            Some(Unary::Get(None, target, name)) => {
+                let property_target = PropertyTarget {
+                    target: target.as_ref().clone(),
+                    name: name.clone(),
+                };
+                let value = self.expression()?;
+                Ok(Statement::SetProperty(property_target, RVal { expr: value }))
+            }
+            _ => ErrBuilder::new()
+                .at(expr.span())
+                .of_type(ParsingErr)
+                .with_message("Invalid assignment target.".to_string())
+                .to_result(),
+        }
+    }
+
     fn var_stmt(&self) -> LoxResult<Statement> {
         let info = "parsing assignment statement";
         self.consume_kwd(Kwd::Var, info)?;
 
         let next_token = self.token_reader.advance_or(self.expected_next_token_err(info))?;
         if !next_token.is_identifier() {
-            return self.parsing_err().expected_but_found("identifier", next_token).to_result();
+            return self.parsing_err().expected_but_found("identifier", &next_token.val).to_result();
         }
 
         self.consume_punct(Equal, info)?;
@@ -274,9 +659,13 @@ impl Parser {
         Ok(Statement::If(condition, inside_if))
     }
 
+    fn block_stmt(&self) -> LoxResult<Statement> {
+        Ok(Statement::Block(self.scoped_program()?))
+    }
+
     fn expression(&self) -> LoxResult<Expr> {
         let eq: Eqlty = self.equality()?;
-        Ok(Expr::Eqlty(eq))
+        Ok(Expr::Eqlty(Box::new(eq)))
     }
 
     fn equality(&self) -> LoxResult<Eqlty> {
@@ -301,11 +690,131 @@ impl Parser {
 
     fn unary(&self) -> LoxResult<Unary> {
         let unary_kind = self.unary_decider()?;
-        match unary_kind {
+        let unary = match unary_kind {
             UnaryKind::Call(with_unary) => self.unary_call(with_unary),
             UnaryKind::Final(with_unary) => self.unary_final(with_unary),
             UnaryKind::Recursive(with_unary) => self.unary_recursive(with_unary),
+            UnaryKind::List(with_unary) => self.unary_list(with_unary),
+            UnaryKind::Map(with_unary) => self.unary_map(with_unary),
+        }?;
+        self.unary_postfix(unary)
+    }
+
+    /// Wraps `unary` in any number of trailing `[index]`, `.name` or `.name(args)`. The unary
+    /// operator applies to the final value.
+    fn unary_postfix(&self, mut unary: Unary) -> LoxResult<Unary> {
+        let info = "parsing index or property access";
+        let is_postfix = |t: &Token| t.equals(LeftBracket) || t.equals(Dot);
+        while let Some(postfix) = self.token_reader.advance_if(is_postfix) {
+            let (op, target) = unary.split_op();
+            let target = Box::new(target);
+
+            if postfix.equals(LeftBracket) {
+                let index = self.expression()?;
+                self.consume_punct(RightBracket, info)?;
+                let span = self.span_from(target.span());
+                unary = Unary::Index(op, target, span, Box::new(index));
+                continue;
+            }
+
+            let name = self
+                .token_reader
+                .advance_or(self.expected_next_token_err(info))?
+                .clone();
+            if !name.is_identifier() {
+                return self
+                    .parsing_err()
+                    .expected_but_found("property name", name)
+                    .while_(info)
+                    .to_result();
+            }
+
+            let is_call = self
+                .token_reader
+                .peek()
+                .map(|t| t.equals(LeftParen))
+                .unwrap_or(false);
+            unary = match is_call {
+                true => {
+                    let args = self.fn_arguments()?;
+                    let span = self.span_from(target.span());
+                    Unary::MethodCall(op, target, name, args, span)
+                }
+                false => Unary::Get(op, target, name),
+            };
         }
+        Ok(unary)
+    }
+
+    fn unary_list(&self, with_unary: bool) -> LoxResult<Unary> {
+        let info = "parsing list literal";
+        let unary = match with_unary {
+            true => Some(
+                self.token_reader
+                    .advance_or(self.expected_next_token_err(info))?
+                    .clone(),
+            ),
+            false => None,
+        };
+        let start = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?
+            .span;
+        self.consume_punct(LeftBracket, info)?;
+
+        let mut items = Vec::new();
+        let reached_end = || {
+            self.token_reader
+                .peek()
+                .map(|t| t.equals(RightBracket))
+                .unwrap_or(false)
+        };
+        while !reached_end() {
+            items.push(self.expression()?);
+            if self.token_reader.advance_if(|t| t.equals(Comme)).is_none() {
+                break;
+            }
+        }
+
+        self.consume_punct(RightBracket, info)?;
+        Ok(Unary::List(unary, self.span_from(start), items))
+    }
+
+    fn unary_map(&self, with_unary: bool) -> LoxResult<Unary> {
+        let info = "parsing map literal";
+        let unary = match with_unary {
+            true => Some(
+                self.token_reader
+                    .advance_or(self.expected_next_token_err(info))?
+                    .clone(),
+            ),
+            false => None,
+        };
+        let start = self
+            .token_reader
+            .peek_or(self.expected_next_token_err(info))?
+            .span;
+        self.consume_punct(LeftBrace, info)?;
+
+        let mut entries = Vec::new();
+        let reached_end = || {
+            self.token_reader
+                .peek()
+                .map(|t| t.equals(RightBrace))
+                .unwrap_or(false)
+        };
+        while !reached_end() {
+            let key = self.expression()?;
+            self.consume_punct(Colon, info)?;
+            let value = self.expression()?;
+            entries.push((key, value));
+            if self.token_reader.advance_if(|t| t.equals(Comme)).is_none() {
+                break;
+            }
+        }
+
+        self.consume_punct(RightBrace, info)?;
+        Ok(Unary::Map(unary, self.span_from(start), entries))
     }
 
     fn unary_final(&self, with_unary: bool) -> LoxResult<Unary> {
@@ -319,8 +828,17 @@ impl Parser {
         };
         let val = self
             .token_reader
-            .advance_or(self.expected_next_token_err("unary final"))?
+            .peek_or(self.expected_next_token_err("unary final"))?
             .clone();
+        let is_operand = val.is_identifier() || val.is_value() || val.equals(Kwd::This);
+        if !is_operand {
+            return ErrBuilder::new()
+                .at(val.span)
+                .of_type(ParsingErr)
+                .with_message(format!("Expected an expression, but found {:?}.", val.val))
+                .to_result();
+        }
+        self.token_reader.advance();
         Ok(Unary::Final(unary, val))
     }
 
@@ -333,8 +851,9 @@ impl Parser {
             ),
             false => None,
         };
+        let start = self.next_span();
         let expr = self.parenthesized_expr()?;
-        Ok(Unary::Recursive(unary, Box::new(expr)))
+        Ok(Unary::Recursive(unary, Box::new(expr), self.span_from(start)))
     }
 
     fn unary_call(&self, with_unary: bool) -> LoxResult<Unary> {
@@ -353,7 +872,7 @@ impl Parser {
         )?;
         let args = self.fn_arguments()?;
 
-        Ok(Unary::Call(unary, identifier.clone(), args))
+        Ok(Unary::Call(unary, identifier.clone(), args, self.span_from(identifier.span)))
     }
 
     fn unary_decider(&self) -> LoxResult<UnaryKind> {
@@ -374,6 +893,14 @@ impl Parser {
                 return Ok(UnaryKind::Recursive(with_op));
             }
 
+            if token_2.equals(Punct::LeftBracket) {
+                return Ok(UnaryKind::List(with_op));
+            }
+
+            if token_2.equals(Punct::LeftBrace) {
+                return Ok(UnaryKind::Map(with_op));
+            }
+
             let token_3 = maybe_token_3.ok_or(
                 self.expected_next_token_err("Parsing third token of an unary expression"),
             )?;
@@ -390,6 +917,14 @@ impl Parser {
             return Ok(UnaryKind::Recursive(with_op));
         }
 
+        if token_1.equals(Punct::LeftBracket) {
+            return Ok(UnaryKind::List(with_op));
+        }
+
+        if token_1.equals(Punct::LeftBrace) {
+            return Ok(UnaryKind::Map(with_op));
+        }
+
         let is_function_call = token_1.is_identifier() && token_2.equals(Punct::LeftParen);
         if is_function_call {
             return Ok(UnaryKind::Call(with_op));
@@ -399,10 +934,14 @@ impl Parser {
             return Ok(UnaryKind::Final(with_op));
         }
 
-        panic!(
-            "Not recognized unary :(, [{:?}, {:?}, {:?}]",
-            token_1, token_2, maybe_token_3
-        );
+        ErrBuilder::new()
+            .at(token_2.span)
+            .of_type(ParsingErr)
+            .with_message(format!(
+                "Expected an operator between {:?} and {:?}.",
+                token_1.val, token_2.val
+            ))
+            .to_result()
     }
 
     fn fn_arguments(&self) -> LoxResult<Vec<Box<Expr>>> {
@@ -473,15 +1012,15 @@ impl Parser {
         Ok(Rule::from_sub(first_sub_rule, sub_rules))
     }
 
-    fn parsing_err(&self) -> ErrBuilder {
+    fn parsing_err(&self) -> ErrBuilder<ErrType, Span> {
         let relevant_token = self
             .token_reader
             .previous()
-            .or(self.token_reader.peek())
-            .unwrap_or_else(|| {
-                panic!("Failed to find the first token while generating error message.")
-            });
-        ErrBuilder::new().at(relevant_token.pos).of_type(ParsingErr)
+            .or(self.token_reader.peek());
+        match relevant_token {
+            Some(token) => ErrBuilder::new().at(token.span).of_type(ParsingErr),
+            None => ErrBuilder::new().without_pos().of_type(ParsingErr),
+        }
     }
 
     fn expected_next_token_err(&self, info: &str) -> LoxError {
@@ -506,7 +1045,7 @@ impl Parser {
 
         Err(self
             .parsing_err()
-            .expected_but_found(exp2, next)
+            .expected_but_found(exp2, &next.val)
             .while_(info)
             .build())
     }
@@ -526,7 +1065,7 @@ impl Parser {
 
         Err(self
             .parsing_err()
-            .expected_but_found(exp2, next)
+            .expected_but_found(exp2, &next.val)
             .while_(info)
             .build())
     }
@@ -539,7 +1078,7 @@ impl Parser {
                 .build(),
         )?;
         match &token.val {
-            TokenValue::Id(id) => Ok((id.clone(), token.pos)),
+            TokenValue::Id(id) => Ok((id.clone(), token.pos())),
             _ => self
                 .parsing_err()
                 .expected_but_found("identifier", token)
@@ -548,3 +1087,26 @@ impl Parser {
         }
     }
 }
+
+/// Keywords after which the parser resumes when skipping a statement with an error.
+fn starts_statement(token: &Token) -> bool {
+    match &token.val {
+        TokenValue::Kwd(kwd) => matches!(
+            kwd,
+            Kwd::Var
+                | Kwd::Fun
+                | Kwd::Class
+                | Kwd::If
+                | Kwd::While
+                | Kwd::For
+                | Kwd::Return
+                | Kwd::Print
+                | Kwd::Try
+                | Kwd::Throw
+                | Kwd::Yield
+                | Kwd::Break
+                | Kwd::Continue
+        ),
+        _ => false,
+    }
+}
@@ -19,7 +19,7 @@ impl PrettyPrint for Program {
     fn pretty_print(&self, pad: u8) {
         println!("Program: ");
         for stmt in self.iter() {
-            stmt.pretty_print(pad + 1);
+            stmt.statement.pretty_print(pad + 1);
             print!("\n");
         }
     }
@@ -36,20 +36,64 @@ impl PrettyPrint for Statement {
                 print_with_pad("return of:".to_string(), pad, false);
                 expr.pretty_print(pad + 1)
             }
+            Self::Yield(_, expr) => {
+                print_with_pad("yield of:".to_string(), pad, false);
+                expr.pretty_print(pad + 1)
+            }
+            Self::Throw(_, expr) => {
+                print_with_pad("throw of:".to_string(), pad, false);
+                expr.pretty_print(pad + 1)
+            }
+            Self::Try(try_stmt) => {
+                try_stmt.body.pretty_print(pad + 1);
+                if let Some((error, handler)) = &try_stmt.catch {
+                    print_with_pad(format!("catch {:?}:", error), pad, true);
+                    handler.pretty_print(pad + 1);
+                }
+                if let Some(cleanup) = &try_stmt.finally {
+                    print_with_pad("finally:".to_string(), pad, true);
+                    cleanup.pretty_print(pad + 1);
+                }
+            }
             Self::Let(lval, rval) => {
                 print_with_pad(format!("{:?}", lval.identifier), pad, true);
                 rval.expr.pretty_print(pad + 1);
             }
+            Self::SetIndex(index_target, rval) => {
+                index_target.target.pretty_print(pad + 1);
+                index_target.index.pretty_print(pad + 1);
+                rval.expr.pretty_print(pad + 1);
+            }
+            Self::SetProperty(property_target, rval) => {
+                property_target.target.pretty_print(pad + 1);
+                print_with_pad(format!(". {:?}", property_target.name), pad + 1, true);
+                rval.expr.pretty_print(pad + 1);
+            }
+            Self::ForIn(label, variable, iterable, prog) => {
+                if let Some(label) = label {
+                    print_with_pad(format!("label: {:?}", label), pad + 1, true);
+                }
+                print_with_pad(format!("{:?} in:", variable), pad + 1, true);
+                iterable.pretty_print(pad + 1);
+                prog.pretty_print(pad + 1)
+            }
             Self::Print(expr) => expr.pretty_print(pad + 1),
             Self::Expr(expr) => expr.pretty_print(pad + 1),
+            Self::Block(prog) => prog.pretty_print(pad + 1),
             Self::If(cond, prog) => {
                 cond.pretty_print(pad + 1);
                 prog.pretty_print(pad + 1)
             }
-            Self::WhileLoop(cond, prog) => {
+            Self::WhileLoop(label, cond, prog) => {
+                if let Some(label) = label {
+                    print_with_pad(format!("label: {:?}", label), pad + 1, true);
+                }
                 cond.pretty_print(pad + 1);
                 prog.pretty_print(pad + 1)
             }
+            Self::Break(_, label) | Self::Continue(_, label) => {
+                print_with_pad(format!("label: {:?}", label), pad + 1, true);
+            }
             Self::Fun(_, function_definition) => {
                 function_definition
                     .args
@@ -67,8 +111,9 @@ impl PrettyPrint for Expr {
         print_with_pad(self.type_name(), pad, true);
         match self {
             Self::Eqlty(eqlty) => eqlty.pretty_print(pad + 1),
-            Self::Call(_, _) => {
-                panic!("todo: impl")
+            Self::Call(callee, args) => {
+                print_with_pad(format!("call of {:?} with:", callee), pad, true);
+                args.iter().for_each(|arg| arg.pretty_print(pad + 1));
             }
         }
     }
@@ -128,12 +173,38 @@ impl PrettyPrint for Unary {
             Self::Final(op, val) => {
                 print_with_pad(format!("[ {:?} | {:?} ]", op, val), pad + 1, true)
             }
-            Self::Recursive(op, expr) => {
+            Self::Recursive(op, expr, _) => {
                 print_with_pad(format!("{:?}", op), pad, true);
                 expr.pretty_print(pad + 1)
             }
-            Self::Call(op, token, expr) => {
-                todo!()
+            Self::Call(op, name, args, _) => {
+                print_with_pad(format!("{:?} call of {:?} with:", op, name), pad, true);
+                args.iter().for_each(|arg| arg.pretty_print(pad + 1));
+            }
+            Self::List(op, _, items) => {
+                print_with_pad(format!("{:?} list of:", op), pad, true);
+                items.iter().for_each(|item| item.pretty_print(pad + 1));
+            }
+            Self::Map(op, _, entries) => {
+                print_with_pad(format!("{:?} map of:", op), pad, true);
+                for (key, value) in entries {
+                    key.pretty_print(pad + 1);
+                    value.pretty_print(pad + 2);
+                }
+            }
+            Self::Index(op, target, _, index) => {
+                print_with_pad(format!("{:?} index of:", op), pad, true);
+                target.pretty_print(pad + 1);
+                index.pretty_print(pad + 1);
+            }
+            Self::Get(op, target, name) => {
+                print_with_pad(format!("{:?} property {:?} of:", op, name), pad, true);
+                target.pretty_print(pad + 1);
+            }
+            Self::MethodCall(op, target, name, args, _) => {
+                print_with_pad(format!("{:?} method {:?} of:", op, name), pad, true);
+                target.pretty_print(pad + 1);
+                args.iter().for_each(|arg| arg.pretty_print(pad + 2));
             }
         }
     }
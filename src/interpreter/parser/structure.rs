@@ -1,20 +1,74 @@
 use super::visitor::*;
-use crate::interpreter::parser::Position;
+use crate::interpreter::parser::Span;
 use crate::interpreter::parser::Token;
 
-pub type Program = Vec<Statement>;
+pub type Program = Vec<Stmt>;
 pub type SubRules<A> = Vec<(Token, A)>;
 
+/// Nodes which know the part of the source they were parsed from.
+pub trait Spanned {
+    fn span(&self) -> Span;
+}
+
+/// A statement with its span, which doesn't include the `;` after it.
+#[derive(Debug, Clone)]
+pub struct Stmt {
+    pub statement: Statement,
+    pub span: Span,
+}
+
 #[derive(Debug, Clone)]
 pub enum Statement {
     Expr(Expr),
     Print(Expr),
     If(Expr, Program),
+    Block(Program),
     Let(LVal, RVal),
-    WhileLoop(Expr, Program),
-    Fun(Position, FunctionDefinition),
+    SetIndex(IndexTarget, RVal),
+    SetProperty(PropertyTarget, RVal),
+    WhileLoop(Option<Token>, Expr, Program),
+    ForIn(Option<Token>, Token, Expr, Program),
+    Fun(Span, FunctionDefinition),
     Return(Expr),
+    Yield(Span, Expr),
+    Throw(Span, Expr),
+    Try(TryStatement),
     Class(ClassDefinition),
+    Break(Span, Option<Token>),
+    Continue(Span, Option<Token>),
+}
+
+impl Statement {
+    /// Statements which end with a block do not need to be followed by a `;`.
+    pub fn is_self_terminating(&self) -> bool {
+        matches!(
+            self,
+            Self::If(_, _)
+                | Self::Block(_)
+                | Self::WhileLoop(_, _, _)
+                | Self::ForIn(_, _, _, _)
+                | Self::Fun(_, _)
+                | Self::Class(_)
+                | Self::Try(_)
+        )
+    }
+
+    /// Whether running the statement may define a function or class, which keeps pointing into
+    /// the source after the run.
+    pub fn defines_functions(&self) -> bool {
+        let any = |program: &Program| program.iter().any(|stmt| stmt.statement.defines_functions());
+        match self {
+            Self::Fun(_, _) | Self::Class(_) => true,
+            Self::If(_, body) | Self::Block(body) | Self::WhileLoop(_, _, body) => any(body),
+            Self::ForIn(_, _, _, body) => any(body),
+            Self::Try(try_stmt) => {
+                any(&try_stmt.body)
+                    || try_stmt.catch.as_ref().is_some_and(|(_, handler)| any(handler))
+                    || try_stmt.finally.as_ref().is_some_and(any)
+            }
+            _ => false,
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -22,11 +76,19 @@ pub enum StatementKind {
     Expr,
     Print,
     If,
+    Block,
     Let,
     WhileLoop,
+    ForIn,
     Fun,
     Return,
+    Yield,
+    Throw,
+    Try,
     Class,
+    Labeled,
+    Break,
+    Continue,
 }
 
 #[derive(Debug, Clone)]
@@ -34,6 +96,16 @@ pub struct FunctionDefinition {
     pub name: Token,
     pub args: Vec<Token>,
     pub body: Program,
+    /// Whether the body contains `yield`. Calling a generator returns a suspended iterator instead of running it.
+    pub is_generator: bool,
+}
+
+/// `try { body } catch (error) { handler } finally { cleanup }`, where either `catch` or `finally` can be omitted.
+#[derive(Debug, Clone)]
+pub struct TryStatement {
+    pub body: Program,
+    pub catch: Option<(Token, Program)>,
+    pub finally: Option<Program>,
 }
 
 #[derive(Debug, Clone)]
@@ -53,9 +125,25 @@ pub struct RVal {
     pub expr: Expr,
 }
 
+/// Left side of `xs[i] = v`.
+#[derive(Debug, Clone)]
+pub struct IndexTarget {
+    pub target: Unary,
+    /// From the start of the target to the closing bracket.
+    pub span: Span,
+    pub index: Expr,
+}
+
+/// Left side of `obj.name = v`.
+#[derive(Debug, Clone)]
+pub struct PropertyTarget {
+    pub target: Unary,
+    pub name: Token,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
-    Eqlty(Eqlty),
+    Eqlty(Box<Eqlty>),
     Call(Token, Vec<Expr>),
 }
 
@@ -63,31 +151,42 @@ pub enum Expr {
 pub struct Eqlty {
     pub first: Comp,
     pub rest: SubRules<Comp>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Comp {
     pub first: Term,
     pub rest: SubRules<Term>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Term {
     pub first: Factor,
     pub rest: SubRules<Factor>,
+    pub span: Span,
 }
 
 #[derive(Debug, Clone)]
 pub struct Factor {
     pub first: Unary,
     pub rest: SubRules<Unary>,
+    pub span: Span,
 }
 
+/// The `Span`s cover the operand, from its first token to its closing bracket or parenthesis,
+/// but not the unary operator.
 #[derive(Debug, Clone)]
 pub enum Unary {
     Final(Option<Token>, Token),
-    Recursive(Option<Token>, Box<Expr>),
-    Call(Option<Token>, Token, Vec<Box<Expr>>),
+    Recursive(Option<Token>, Box<Expr>, Span),
+    Call(Option<Token>, Token, Vec<Box<Expr>>, Span),
+    List(Option<Token>, Span, Vec<Expr>),
+    Map(Option<Token>, Span, Vec<(Expr, Expr)>),
+    Index(Option<Token>, Box<Unary>, Span, Box<Expr>),
+    Get(Option<Token>, Box<Unary>, Token),
+    MethodCall(Option<Token>, Box<Unary>, Token, Vec<Box<Expr>>, Span),
 }
 
 #[derive(Debug, Clone)]
@@ -95,6 +194,104 @@ pub enum UnaryKind {
     Final(bool),
     Recursive(bool),
     Call(bool),
+    List(bool),
+    Map(bool),
+}
+
+impl Expr {
+    /// Returns the only unary of the expression, if it has no binary operators.
+    pub fn as_unary(&self) -> Option<&Unary> {
+        let Expr::Eqlty(eqlty) = self else {
+            return None;
+        };
+        let comp = &eqlty.first;
+        let term = &comp.first;
+        let factor = &term.first;
+        let no_operators = eqlty.rest.is_empty()
+            && comp.rest.is_empty()
+            && term.rest.is_empty()
+            && factor.rest.is_empty();
+
+        no_operators.then_some(&factor.first)
+    }
+}
+
+impl Unary {
+    /// Separates the unary operator from the operand, so postfix operations can be applied before it.
+    pub fn split_op(self) -> (Option<Token>, Unary) {
+        match self {
+            Unary::Final(op, token) => (op, Unary::Final(None, token)),
+            Unary::Recursive(op, expr, span) => (op, Unary::Recursive(None, expr, span)),
+            Unary::Call(op, name, args, span) => (op, Unary::Call(None, name, args, span)),
+            Unary::List(op, span, items) => (op, Unary::List(None, span, items)),
+            Unary::Map(op, span, entries) => (op, Unary::Map(None, span, entries)),
+            Unary::Index(op, target, span, index) => (op, Unary::Index(None, target, span, index)),
+            Unary::Get(op, target, name) => (op, Unary::Get(None, target, name)),
+            Unary::MethodCall(op, target, name, args, span) => {
+                (op, Unary::MethodCall(None, target, name, args, span))
+            }
+        }
+    }
+}
+
+// #################################
+
+impl Spanned for Stmt {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Expr {
+    fn span(&self) -> Span {
+        match self {
+            Expr::Eqlty(eqlty) => eqlty.span,
+            Expr::Call(callee, _) => callee.span,
+        }
+    }
+}
+
+impl Spanned for Eqlty {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Comp {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Term {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Factor {
+    fn span(&self) -> Span {
+        self.span
+    }
+}
+
+impl Spanned for Unary {
+    fn span(&self) -> Span {
+        let (op, operand) = match self {
+            Unary::Final(op, token) => (op, token.span),
+            Unary::Recursive(op, _, span)
+            | Unary::Call(op, _, _, span)
+            | Unary::List(op, span, _)
+            | Unary::Map(op, span, _)
+            | Unary::Index(op, _, span, _)
+            | Unary::MethodCall(op, _, _, _, span) => (op, *span),
+            Unary::Get(op, target, name) => (op, target.span().to(name.span)),
+        };
+        match op {
+            Some(op) => op.span.to(operand),
+            None => operand,
+        }
+    }
 }
 
 // #################################
@@ -105,36 +302,56 @@ pub trait FromSubRules<A> {
 
 impl FromSubRules<Comp> for Eqlty {
     fn from_sub(first: Comp, rest: SubRules<Comp>) -> Self {
+        let span = match rest.last() {
+            Some((_, last)) => first.span().to(last.span()),
+            None => first.span(),
+        };
         Eqlty {
             first: first,
             rest: rest,
+            span,
         }
     }
 }
 
 impl FromSubRules<Term> for Comp {
     fn from_sub(first: Term, rest: SubRules<Term>) -> Self {
+        let span = match rest.last() {
+            Some((_, last)) => first.span().to(last.span()),
+            None => first.span(),
+        };
         Comp {
             first: first,
             rest: rest,
+            span,
         }
     }
 }
 
 impl FromSubRules<Factor> for Term {
     fn from_sub(first: Factor, rest: SubRules<Factor>) -> Self {
+        let span = match rest.last() {
+            Some((_, last)) => first.span().to(last.span()),
+            None => first.span(),
+        };
         Term {
             first: first,
             rest: rest,
+            span,
         }
     }
 }
 
 impl FromSubRules<Unary> for Factor {
     fn from_sub(first: Unary, rest: SubRules<Unary>) -> Self {
+        let span = match rest.last() {
+            Some((_, last)) => first.span().to(last.span()),
+            None => first.span(),
+        };
         Factor {
             first: first,
             rest: rest,
+            span,
         }
     }
 }
@@ -196,10 +413,19 @@ impl NamedType for Statement {
             Self::Print(_) => "Statement",
             Self::Expr(_) => "Expression",
             Self::If(_, _) => "IfStatement",
+            Self::Block(_) => "Block",
             Self::Let(_, _) => "LetStmt",
-            Self::WhileLoop(_, _) => "WhileLoop",
+            Self::SetIndex(_, _) => "SetIndex",
+            Self::SetProperty(_, _) => "SetProperty",
+            Self::ForIn(_, _, _, _) => "ForIn",
+            Self::WhileLoop(_, _, _) => "WhileLoop",
             Self::Fun(_, _) => "Fun",
             Self::Return(_) => "Return",
+            Self::Yield(_, _) => "Yield",
+            Self::Throw(_, _) => "Throw",
+            Self::Try(_) => "Try",
+            Self::Break(_, _) => "Break",
+            Self::Continue(_, _) => "Continue",
         }
         .to_string()
     }
@@ -5,8 +5,10 @@ use std::cell::Cell;
 pub struct TextReader {
     source: Vec<char>,
     pos: Cell<usize>,
-    current_line: Cell<usize>,
-    line_pos: Cell<usize>,
+    current_line: Cell<u32>,
+    line_pos: Cell<u32>,
+    /// Byte offset of the character at `pos`.
+    offset: Cell<u32>,
 }
 
 impl TextReader {
@@ -14,6 +16,7 @@ impl TextReader {
         Position {
             line: self.current_line.get(),
             line_pos: self.line_pos.get(),
+            offset: self.offset.get(),
         }
     }
 }
@@ -25,6 +28,7 @@ impl ReaderBase<char> for TextReader {
             pos: Cell::new(0),
             current_line: Cell::new(0),
             line_pos: Cell::new(0),
+            offset: Cell::new(0),
         }
     }
 
@@ -38,6 +42,7 @@ impl ReaderBase<char> for TextReader {
         if let Some(c) = output {
             self.pos.set(pos + 1);
             self.line_pos.set(self.line_pos.get() + 1);
+            self.offset.set(self.offset.get() + c.len_utf8() as u32);
 
             if *c == '\n' {
                 self.current_line.set(self.current_line.get() + 1);
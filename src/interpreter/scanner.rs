@@ -1,31 +1,60 @@
 //! The scanner. Basically a pure function from a `String` to a `Vec<Token>`.
 
 use crate::interpreter::errors::position::Position;
-use crate::interpreter::errors::{ErrBuilder, ErrType::ScanningErr, LoxResult};
+use crate::interpreter::errors::span::FileId;
+use crate::interpreter::errors::{ErrBuilder, ErrType::ScanningErr, LoxError, LoxResult};
 use crate::interpreter::tokens::TokenValue;
 
 use crate::interpreter::readers::{Reader, TextReader};
 use crate::interpreter::tokens::{Equals, Punct::*, Token, Tokenizable};
+use std::cell::RefCell;
 
+/// All tokens of the source, with `TokenValue::Error` tokens where `errors` were found.
 pub struct ScannerOutput {
     pub tokens: Vec<Token>,
+    pub errors: Vec<LoxError>,
+}
+
+impl ScannerOutput {
+    /// The tokens, if there were no errors, or the first of the errors.
+    pub fn into_result(mut self) -> LoxResult<Self> {
+        match self.errors.is_empty() {
+            true => Ok(self),
+            false => Err(self.errors.remove(0)),
+        }
+    }
 }
 
 pub struct Scanner {
     reader: TextReader,
+    errors: RefCell<Vec<LoxError>>,
+    file_id: FileId,
 }
 
 impl Scanner {
     pub fn new(source: String) -> Self {
         Scanner {
             reader: TextReader::from_vec(source.chars().collect()),
+            errors: RefCell::new(Vec::new()),
+            file_id: 0,
         }
     }
 
-    pub fn scan(self) -> LoxResult<ScannerOutput> {
+    /// Tags the spans of tokens and errors with `file_id`, the id of the source in the `SourceMap`.
+    pub fn in_file(mut self, file_id: FileId) -> Self {
+        self.file_id = file_id;
+        self
+    }
+
+    /// Scans the whole source. Errors don't stop the scanning, they're collected instead.
+    pub fn scan(self) -> ScannerOutput {
         let mut tokens = Vec::new();
         loop {
-            let token = self.next_token()?;
+            let mut token = self
+                .next_token()
+                .unwrap_or_else(|err| self.error_token(String::new(), err));
+            token.span.end = self.reader.curr_pos();
+            token.span.file_id = self.file_id;
             let is_eof = token.equals(Eof);
             tokens.push(token);
 
@@ -33,7 +62,21 @@ impl Scanner {
                 break;
             }
         }
-        Ok(ScannerOutput { tokens: tokens })
+        let mut errors = self.errors.take();
+        for err in errors.iter_mut() {
+            err.span.file_id = self.file_id;
+        }
+        ScannerOutput {
+            tokens: tokens,
+            errors,
+        }
+    }
+
+    /// Records `err`, and returns a token which stands in for `lexeme` so scanning can go on.
+    fn error_token(&self, lexeme: String, err: LoxError) -> Token {
+        let token = Token::new(TokenValue::Error(lexeme), err.pos());
+        self.errors.borrow_mut().push(err);
+        token
     }
 
     fn next_token(&self) -> LoxResult<Token> {
@@ -45,31 +88,35 @@ impl Scanner {
                 ')' => Ok(RightParen.at(pos)),
                 '{' => Ok(LeftBrace.at(pos)),
                 '}' => Ok(RightBrace.at(pos)),
+                '[' => Ok(LeftBracket.at(pos)),
+                ']' => Ok(RightBracket.at(pos)),
                 ',' => Ok(Comme.at(pos)),
+                ':' => Ok(Colon.at(pos)),
                 '.' => Ok(Dot.at(pos)),
                 '-' => Ok(Minus.at(pos)),
                 '+' => Ok(Plus.at(pos)),
                 '%' => Ok(Mod.at(pos)),
                 ';' => Ok(Semicolon.at(pos)),
                 '*' => Ok(Star.at(pos)),
-                '!' => self.handle_bang(),
-                '=' => self.handle_eq(),
-                '>' => self.handle_gr(),
-                '<' => self.handle_le(),
-                '/' => self.handle_slash(),
+                '!' => self.handle_bang(pos),
+                '=' => self.handle_eq(pos),
+                '>' => self.handle_gr(pos),
+                '<' => self.handle_le(pos),
+                '/' => self.handle_slash(pos),
                 ' ' => self.next_token(),
                 '\t' => self.next_token(),
                 '\n' => self.next_token(),
                 '\r' => self.next_token(),
                 _ => {
                     if is_valid_variable_char(c) {
-                        self.handle_literal(c)
+                        self.handle_literal(c, pos)
                     } else {
-                        ErrBuilder::new()
+                        let err = ErrBuilder::new()
                             .at(pos)
                             .of_type(ScanningErr)
-                            .with_message(format!("Unrecognized character {}", *c as u32))
-                            .to_result()
+                            .with_message(format!("Unrecognized character {:?}", c))
+                            .build();
+                        Ok(self.error_token(c.to_string(), err))
                     }
                 }
             },
@@ -77,31 +124,34 @@ impl Scanner {
         }
     }
 
-    fn handle_literal(&self, first_char: &char) -> LoxResult<Token> {
+    fn handle_literal(&self, first_char: &char, pos: Position) -> LoxResult<Token> {
         if *first_char == '"' {
-            return self.handle_string_literal(first_char);
+            return self.handle_string_literal(first_char, pos);
         }
 
-        self.handle_var_or_val_literal(first_char)
+        self.handle_var_or_val_literal(first_char, pos)
     }
 
-    fn handle_string_literal(&self, first_char: &char) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
-
+    fn handle_string_literal(&self, first_char: &char, pos: Position) -> LoxResult<Token> {
         let mut buffer = String::new();
         buffer.push(*first_char);
+        let mut is_terminated = false;
         while let Some(c) = self.reader.advance() {
             buffer.push(*c);
             if *c == '"' {
+                is_terminated = true;
                 break;
             }
         }
 
-        Token::from_string(buffer, pos)
+        if !is_terminated {
+            let err = scanning_err(pos, "Unterminated string".to_string());
+            return Ok(self.error_token(buffer, err));
+        }
+        self.token_from(buffer, pos)
     }
 
-    fn handle_var_or_val_literal(&self, first_char: &char) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
+    fn handle_var_or_val_literal(&self, first_char: &char, pos: Position) -> LoxResult<Token> {
         let mut buffer = String::new();
         buffer.push(*first_char);
 
@@ -112,12 +162,28 @@ impl Scanner {
             self.reader.advance();
             buffer.push(*c);
         }
-        Token::from_string(buffer, pos)
+
+        if first_char.is_ascii_digit() {
+            if !buffer.chars().all(|c| c.is_ascii_digit()) {
+                let err = scanning_err(pos, format!("Invalid number literal {}", buffer));
+                return Ok(self.error_token(buffer, err));
+            }
+            if buffer.parse::<i16>().is_err() {
+                let err = scanning_err(pos, format!("Number {} does not fit into an integer", buffer));
+                return Ok(self.error_token(buffer, err));
+            }
+        }
+        self.token_from(buffer, pos)
     }
 
-    fn handle_bang(&self) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
+    fn token_from(&self, lexeme: String, pos: Position) -> LoxResult<Token> {
+        match Token::from_string(lexeme.clone(), pos) {
+            Ok(token) => Ok(token),
+            Err(err) => Ok(self.error_token(lexeme, err)),
+        }
+    }
 
+    fn handle_bang(&self, pos: Position) -> LoxResult<Token> {
         match self.reader.peek() {
             Some(c) => match c {
                 '=' => self.advance_and(|| Ok(BangEqual.at(pos.clone()))),
@@ -127,9 +193,7 @@ impl Scanner {
         }
     }
 
-    fn handle_eq(&self) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
-
+    fn handle_eq(&self, pos: Position) -> LoxResult<Token> {
         match self.reader.peek() {
             Some(c) => match c {
                 '=' => self.advance_and(|| Ok(EqualEqual.at(pos.clone()))),
@@ -139,9 +203,7 @@ impl Scanner {
         }
     }
 
-    fn handle_le(&self) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
-
+    fn handle_le(&self, pos: Position) -> LoxResult<Token> {
         match self.reader.peek() {
             Some(c) => match c {
                 '=' => self.advance_and(|| Ok(LessEqual.at(pos.clone()))),
@@ -151,10 +213,8 @@ impl Scanner {
         }
     }
 
-    fn handle_gr(&self) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
-
-        match self.reader.advance() {
+    fn handle_gr(&self, pos: Position) -> LoxResult<Token> {
+        match self.reader.peek() {
             Some(c) => match c {
                 '=' => self.advance_and(|| Ok(GreaterEqual.at(pos.clone()))),
                 _ => Ok(Greater.at(pos)),
@@ -163,12 +223,10 @@ impl Scanner {
         }
     }
 
-    fn handle_slash(&self) -> LoxResult<Token> {
-        let pos = self.reader.curr_pos();
-
-        match self.reader.advance() {
+    fn handle_slash(&self, pos: Position) -> LoxResult<Token> {
+        match self.reader.peek() {
             Some(c) => match c {
-                '/' => self.handle_comment(),
+                '/' => self.advance_and(|| self.handle_comment()),
                 _ => Ok(Slash.at(pos)),
             },
             None => unexpected_eof_err(pos),
@@ -193,6 +251,14 @@ fn is_valid_variable_char(c: &char) -> bool {
     c.is_alphanumeric() || *c == '\'' || *c == '_' || *c == '"'
 }
 
+fn scanning_err(pos: Position, msg: String) -> LoxError {
+    ErrBuilder::new()
+        .at(pos)
+        .of_type(ScanningErr)
+        .with_message(msg)
+        .build()
+}
+
 fn unexpected_eof_err<A>(pos: Position) -> LoxResult<A> {
     ErrBuilder::new()
         .at(pos)
@@ -1,5 +1,6 @@
-use crate::interpreter::errors::{position::Position, ErrBuilder, ErrType::TokenizingErr, LoxResult};
+use crate::interpreter::errors::{position::Position, span::Span, ErrBuilder, ErrType, ErrType::LogicError, ErrType::TokenizingErr, LoxResult};
 use regex::Regex;
+use std::fmt;
 
 pub use kwds::Kwd;
 pub use lox_values::LoxValue;
@@ -18,6 +19,8 @@ pub enum TokenValue {
     Kwd(Kwd),
     Val(LoxValue),
     Id(String),
+    /// Characters which could not be scanned, standing in for them after the error was reported.
+    Error(String),
 }
 
 impl From<Punct> for TokenValue {
@@ -38,38 +41,55 @@ impl From<Kwd> for TokenValue {
     }
 }
 
-/// Token value enhanced with a position
+/// Token value enhanced with the span of its characters
 #[derive(Debug, Clone, Eq, PartialEq)]
 pub struct Token {
     pub val: TokenValue,
-    pub pos: Position,
+    pub span: Span,
+}
+
+/// Identifiers show their name, other tokens their value, for messages shown to users.
+impl fmt::Display for Token {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.val {
+            TokenValue::Id(name) => write!(f, "{}", name),
+            other => write!(f, "{:?}", other),
+        }
+    }
 }
 
 impl Token {
-    pub fn new(tok_val: TokenValue, pos: Position) -> Self {
+    /// The span only covers `pos` until the scanner sets where the token ends.
+    pub fn new(tok_val: TokenValue, pos: impl Into<Span>) -> Self {
         Token {
             val: tok_val,
-            pos: pos,
+            span: pos.into(),
         }
     }
 
+    pub fn pos(&self) -> Position {
+        self.span.start
+    }
+
     pub fn from_string(string: String, position: Position) -> LoxResult<Self> {
-        let create_from = |tv: TokenValue| {
-            Ok(Token {
-                val: tv,
-                pos: position,
-            })
-        };
+        let create_from = |tv: TokenValue| Ok(Token::new(tv, position));
         if string.eq("true") {
             create_from(TokenValue::Val(LoxValue::from(true)))
         } else if string.eq("false") {
             create_from(TokenValue::Val(LoxValue::from(false)))
+        } else if string.eq("nil") {
+            create_from(TokenValue::Val(LoxValue::Nil))
         } else if Kwd::is_valid(&string) {
             let kwd = Kwd::from(&string, position.clone())?;
             create_from(TokenValue::from(kwd))
         } else if Regex::new(NUMBER_RE).unwrap().is_match(&string) {
-            let number: i16 = string.parse().expect("Failed to parse string as number");
-            create_from(TokenValue::from(LoxValue::from(number)))
+            match string.parse::<i16>() {
+                Ok(number) => create_from(TokenValue::from(LoxValue::from(number))),
+                Err(_) => Self::tokenizing_err()
+                    .with_message(format!("Number {} does not fit into an integer", string))
+                    .with_pos(position)
+                    .to_result(),
+            }
         } else if string.starts_with('\"') && string.ends_with('\"') {
             create_from(TokenValue::from(LoxValue::from(string)))
         } else if Regex::new(VARIABLE_RE).unwrap().is_match(&string) {
@@ -86,8 +106,9 @@ impl Token {
         match &self.val {
             TokenValue::Punct(p) => Ok(p.clone()),
             _ => ErrBuilder::new()
-                .at(self.pos)
-                .is_not(self, "a lox value")
+                .of_type(LogicError)
+                .at(self.span)
+                .is_not(&self.val, "a punctuation")
                 .to_result(),
         }
     }
@@ -99,6 +120,24 @@ impl Token {
         }
     }
 
+    pub fn as_identifier(&self) -> LoxResult<&String> {
+        match &self.val {
+            TokenValue::Id(name) => Ok(name),
+            _ => ErrBuilder::new()
+                .of_type(LogicError)
+                .at(self.span)
+                .is_not(&self.val, "an identifier")
+                .to_result(),
+        }
+    }
+
+    pub fn identifier(&self) -> Option<&String> {
+        match &self.val {
+            TokenValue::Id(name) => Some(name),
+            _ => None,
+        }
+    }
+
     pub fn is_value(&self) -> bool {
         match self.val {
             TokenValue::Val(_) => true,
@@ -110,13 +149,14 @@ impl Token {
         match &self.val {
             TokenValue::Val(lox_val) => Ok(lox_val.clone()),
             _ => ErrBuilder::new()
-                .at(self.pos)
-                .is_not(self, "a lox value")
+                .of_type(LogicError)
+                .at(self.span)
+                .is_not(&self.val, "a lox value")
                 .to_result(),
         }
     }
 
-    pub fn tokenizing_err() -> ErrBuilder {
+    pub fn tokenizing_err() -> ErrBuilder<ErrType> {
         ErrBuilder::new().of_type(TokenizingErr)
     }
 
@@ -156,18 +196,12 @@ pub trait Tokenizable {
 
 impl Tokenizable for Punct {
     fn at(self, pos: Position) -> Token {
-        Token {
-            val: TokenValue::from(self),
-            pos: pos,
-        }
+        Token::new(TokenValue::from(self), pos)
     }
 }
 
 impl Tokenizable for LoxValue {
     fn at(self, pos: Position) -> Token {
-        Token {
-            val: TokenValue::from(self),
-            pos: pos,
-        }
+        Token::new(TokenValue::from(self), pos)
     }
 }
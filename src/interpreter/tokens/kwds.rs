@@ -6,21 +6,29 @@ use crate::interpreter::tokens::Position;
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum Kwd {
     And,
+    Break,
+    Catch,
     Class,
+    Continue,
     Else,
     False,
+    Finally,
     Fun,
     For,
     If,
+    In,
     Nil,
     Or,
     Print,
     Return,
     Super,
     This,
+    Throw,
     True,
+    Try,
     Var,
     While,
+    Yield,
     Comment(String),
 }
 
@@ -36,17 +44,25 @@ impl Kwd {
     pub fn from(string: &String, pos: Position) -> LoxResult<Self> {
         match string.as_str() {
             "and" => Ok(Self::And),
+            "break" => Ok(Self::Break),
+            "catch" => Ok(Self::Catch),
             "class" => Ok(Self::Class),
+            "continue" => Ok(Self::Continue),
             "else" => Ok(Self::Else),
+            "finally" => Ok(Self::Finally),
             "fun" => Ok(Self::Fun),
             "for" => Ok(Self::For),
             "if" => Ok(Self::If),
+            "in" => Ok(Self::In),
             "print" => Ok(Self::Print),
             "return" => Ok(Self::Return),
             "super" => Ok(Self::Super),
             "this" => Ok(Self::This),
+            "throw" => Ok(Self::Throw),
+            "try" => Ok(Self::Try),
             "var" => Ok(Self::Var),
             "while" => Ok(Self::While),
+            "yield" => Ok(Self::Yield),
             _ => ErrBuilder::new()
                 .at(pos)
                 .of_type(ScanningErr)
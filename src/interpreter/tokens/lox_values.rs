@@ -1,12 +1,14 @@
 use std::fmt::Debug;
+use std::fmt::Display;
 use std::fmt::Error;
 use std::fmt::Formatter;
 
-#[derive(Clone, Eq, PartialEq)]
+#[derive(Clone, Eq, PartialEq, Hash)]
 pub enum LoxValue {
     Integer(i16),
     Boolean(bool),
     String(String),
+    Nil,
 }
 
 impl Debug for LoxValue {
@@ -15,6 +17,19 @@ impl Debug for LoxValue {
             Self::Integer(x) => write!(f, "Int({})", x),
             Self::Boolean(x) => write!(f, "Bool({})", x),
             Self::String(x) => write!(f, "String({})", x),
+            Self::Nil => write!(f, "Nil"),
+        }
+    }
+}
+
+/// Human readable form, used e.g. when joining values into a string.
+impl Display for LoxValue {
+    fn fmt(&self, f: &mut Formatter) -> Result<(), Error> {
+        match self {
+            Self::Integer(x) => write!(f, "{}", x),
+            Self::Boolean(x) => write!(f, "{}", x),
+            Self::String(x) => write!(f, "{}", x.trim_matches('"')),
+            Self::Nil => write!(f, "nil"),
         }
     }
 }
@@ -43,6 +58,7 @@ impl From<LoxValue> for bool {
             LoxValue::Integer(x) => x != 0,
             LoxValue::Boolean(x) => x,
             LoxValue::String(x) => x.len() > 0,
+            LoxValue::Nil => false,
         }
     }
 }
@@ -4,7 +4,10 @@ pub enum Punct {
     RightParen,
     LeftBrace,
     RightBrace,
+    LeftBracket,
+    RightBracket,
     Comme,
+    Colon,
     Dot,
     Minus,
     Plus,
@@ -24,3 +27,4 @@ pub enum Punct {
 
     Eof,
 }
+
@@ -0,0 +1,262 @@
+//! Values exchanged between Lox and the host application.
+//!
+//! `LoxObj` shares lists, maps and instances between bindings, while `Value` is a plain copy which
+//! the host can take apart. Values without a plain counterpart, e.g. functions and instances, are
+//! kept as `Value::Object` and can only be passed back to Lox.
+
+use std::collections::HashMap;
+use std::convert::{Infallible, TryFrom};
+use std::rc::Rc;
+
+use crate::interpreter::errors::span::Span;
+use crate::interpreter::errors::{ErrBuilder, ErrType, LoxError, LoxResult};
+use crate::interpreter::execute::{LoxObj, OrderedMap};
+use crate::interpreter::tokens::LoxValue;
+
+#[derive(Debug, Clone)]
+pub enum Value {
+    Nil,
+    Bool(bool),
+    Int(i64),
+    String(String),
+    List(Vec<Value>),
+    /// Entries in insertion order. Keys are always `Nil`, `Bool`, `Int` or `String`.
+    Map(Vec<(Value, Value)>),
+    Object(LoxObj),
+}
+
+/// Errors of conversions aren't tied to any place in the program.
+fn conversion_err() -> ErrBuilder<ErrType, Span> {
+    ErrBuilder::new()
+        .of_type(ErrType::RuntimeError)
+        .without_pos()
+}
+
+impl Value {
+    pub fn from_lox(obj: &LoxObj) -> Self {
+        Self::from_lox_inner(obj, &mut Vec::new())
+    }
+
+    /// `path` holds the containers being converted, so that a list containing itself is kept as
+    /// an object the second time instead of being converted forever.
+    fn from_lox_inner(obj: &LoxObj, path: &mut Vec<*const ()>) -> Self {
+        let ptr = match obj {
+            LoxObj::Plain(val) => return Value::from_lox_value(val),
+            LoxObj::List(items) => Rc::as_ptr(items) as *const (),
+            LoxObj::Map(map) => Rc::as_ptr(map) as *const (),
+            _ => return Value::Object(obj.clone()),
+        };
+        if path.contains(&ptr) {
+            return Value::Object(obj.clone());
+        }
+
+        path.push(ptr);
+        let value = match obj {
+            LoxObj::List(items) => Value::List(
+                items
+                    .borrow()
+                    .iter()
+                    .map(|item| Value::from_lox_inner(item, path))
+                    .collect(),
+            ),
+            LoxObj::Map(map) => Value::Map(
+                map.borrow()
+                    .iter()
+                    .map(|(key, value)| {
+                        (Value::from_lox_value(key), Value::from_lox_inner(value, path))
+                    })
+                    .collect(),
+            ),
+            _ => Value::Object(obj.clone()),
+        };
+        path.pop();
+        value
+    }
+
+    fn from_lox_value(val: &LoxValue) -> Self {
+        match val {
+            LoxValue::Integer(x) => Value::Int(*x as i64),
+            LoxValue::Boolean(x) => Value::Bool(*x),
+            LoxValue::String(x) => {
+                let unquoted = x.strip_prefix('"').and_then(|x| x.strip_suffix('"'));
+                Value::String(unquoted.unwrap_or(x).to_string())
+            }
+            LoxValue::Nil => Value::Nil,
+        }
+    }
+
+    /// Fails for integers which don't fit in a Lox integer, and for map keys which aren't plain.
+    pub fn to_lox(&self) -> LoxResult<LoxObj> {
+        match self {
+            Value::List(items) => {
+                let items: LoxResult<Vec<LoxObj>> = items.iter().map(|item| item.to_lox()).collect();
+                Ok(LoxObj::list(items?))
+            }
+            Value::Map(entries) => {
+                let mut map = OrderedMap::new();
+                for (key, value) in entries {
+                    map.insert(key.to_lox_value()?, value.to_lox()?);
+                }
+                Ok(LoxObj::map(map))
+            }
+            Value::Object(obj) => Ok(obj.clone()),
+            _ => Ok(LoxObj::Plain(self.to_lox_value()?)),
+        }
+    }
+
+    fn to_lox_value(&self) -> LoxResult<LoxValue> {
+        match self {
+            Value::Nil => Ok(LoxValue::Nil),
+            Value::Bool(x) => Ok(LoxValue::Boolean(*x)),
+            Value::Int(x) => match i16::try_from(*x) {
+                Ok(x) => Ok(LoxValue::Integer(x)),
+                Err(_) => conversion_err()
+                    .with_message(format!("Integer {} does not fit in a Lox integer", x))
+                    .to_result(),
+            },
+            Value::String(x) => Ok(LoxValue::String(format!("\"{}\"", x))),
+            _ => conversion_err().is_not(format!("{:?}", self), "a value").to_result(),
+        }
+    }
+}
+
+impl PartialEq for Value {
+    /// Objects are equal only if they are the same object.
+    fn eq(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Value::Nil, Value::Nil) => true,
+            (Value::Bool(a), Value::Bool(b)) => a == b,
+            (Value::Int(a), Value::Int(b)) => a == b,
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::List(a), Value::List(b)) => a == b,
+            (Value::Map(a), Value::Map(b)) => a == b,
+            (Value::Object(a), Value::Object(b)) => a.equals(b),
+            _ => false,
+        }
+    }
+}
+
+impl From<i64> for Value {
+    fn from(x: i64) -> Self {
+        Value::Int(x)
+    }
+}
+
+impl From<i32> for Value {
+    fn from(x: i32) -> Self {
+        Value::Int(x as i64)
+    }
+}
+
+impl From<bool> for Value {
+    fn from(x: bool) -> Self {
+        Value::Bool(x)
+    }
+}
+
+impl From<String> for Value {
+    fn from(x: String) -> Self {
+        Value::String(x)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(x: &str) -> Self {
+        Value::String(x.to_string())
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(items: Vec<T>) -> Self {
+        Value::List(items.into_iter().map(Into::into).collect())
+    }
+}
+
+impl<T: Into<Value>> From<HashMap<String, T>> for Value {
+    fn from(map: HashMap<String, T>) -> Self {
+        Value::Map(
+            map.into_iter()
+                .map(|(key, value)| (Value::String(key), value.into()))
+                .collect(),
+        )
+    }
+}
+
+impl TryFrom<Value> for i64 {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Int(x) => Ok(x),
+            _ => conversion_err().is_not(format!("{:?}", value), "integer").to_result(),
+        }
+    }
+}
+
+impl TryFrom<Value> for bool {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Bool(x) => Ok(x),
+            _ => conversion_err().is_not(format!("{:?}", value), "boolean").to_result(),
+        }
+    }
+}
+
+impl TryFrom<Value> for String {
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::String(x) => Ok(x),
+            _ => conversion_err().is_not(format!("{:?}", value), "string").to_result(),
+        }
+    }
+}
+
+impl<T> TryFrom<Value> for Vec<T>
+where
+    T: TryFrom<Value>,
+    T::Error: Into<LoxError>,
+{
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::List(items) => items
+                .into_iter()
+                .map(|item| T::try_from(item).map_err(Into::into))
+                .collect(),
+            _ => conversion_err().is_not(format!("{:?}", value), "list").to_result(),
+        }
+    }
+}
+
+impl<T> TryFrom<Value> for HashMap<String, T>
+where
+    T: TryFrom<Value>,
+    T::Error: Into<LoxError>,
+{
+    type Error = LoxError;
+
+    fn try_from(value: Value) -> Result<Self, Self::Error> {
+        match value {
+            Value::Map(entries) => entries
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = T::try_from(value).map_err(Into::into)?;
+                    Ok((String::try_from(key)?, value))
+                })
+                .collect(),
+            _ => conversion_err().is_not(format!("{:?}", value), "map").to_result(),
+        }
+    }
+}
+
+/// Lets `Vec<Value>` and `HashMap<String, Value>` be converted like other collections.
+impl From<Infallible> for LoxError {
+    fn from(never: Infallible) -> Self {
+        match never {}
+    }
+}
@@ -0,0 +1,22 @@
+//! Lox interpreter which can be embedded as a scripting layer.
+//!
+//! ```
+//! use lox::{LoxInterpreter, Value};
+//!
+//! let mut interpreter = LoxInterpreter::new();
+//! interpreter.set_global("limit", 10).unwrap();
+//! let value = interpreter.eval("fun twice(x) { return x * 2; } twice(limit);").unwrap();
+//! assert_eq!(value, Value::Int(20));
+//! ```
+//!
+//! Scripts can run on any thread. Deep recursion grows the native stack as needed and ends in a
+//! catchable stack overflow error once it gets deeper than the call depth limit, see
+//! `LoxInterpreter::with_max_call_depth`.
+
+pub mod interpreter;
+
+pub use interpreter::errors::{ErrType, LoxError};
+pub use interpreter::execute::{Capabilities, Capability, Ctx, HostObject, LoxObj};
+pub use interpreter::output::OutputBuffer;
+pub use interpreter::value::Value;
+pub use interpreter::LoxInterpreter;
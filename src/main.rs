@@ -1,31 +1,153 @@
 use std::env;
+use std::io;
 
-pub mod interpreter;
-use interpreter::LoxInterpreter;
+use lox::interpreter;
+use interpreter::conformance;
+use interpreter::errors::{ErrType, ErrorFormat};
+use interpreter::execute::{Capabilities, Capability};
+use std::path::{Path, PathBuf};
+use interpreter::{with_large_stack, LoxInterpreter};
+
+const USAGE: &str = "Usage: rlox [--allow-read[=<dir>]] [--allow-write[=<dir>]] [--allow-env] \
+[--allow-clock] [--allow-exit] [--allow-stdin] [--allow-all] [--error-format=human|json] [script]
+       rlox test <dir>";
 
 /// Handles arguments from the command line and calls appropiate methods from `LoxInterpreter`.
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let mut interpreter = LoxInterpreter::new();
-    match args.len() {
-        1 => interpreter.run_prompt(),
-        2 => interpreter.run_file(&args[1]).map_or((), |_| ()),
-        _ => {
-            println!("Usage: rlox [script]");
-            std::process::exit(64)
+    let (flags, args): (Vec<String>, Vec<String>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let (format_flags, flags): (Vec<String>, Vec<String>) =
+        flags.into_iter().partition(|flag| flag.starts_with("--error-format"));
+    let capabilities = parse_capabilities(&flags).unwrap_or_else(|flag| unknown_option(&flag));
+    let error_format = parse_error_format(&format_flags).unwrap_or_else(|flag| unknown_option(&flag));
+
+    if args.len() == 2 && args[0] == "test" {
+        let dir = PathBuf::from(&args[1]);
+        return with_large_stack(move || run_tests(&dir));
+    }
+
+    let code = with_large_stack(move || {
+        let mut interpreter = LoxInterpreter::new()
+            .with_capabilities(capabilities)
+            .with_error_format(error_format)
+            .with_diagnostics(io::stderr());
+        let err = match args.len() {
+            0 => interpreter.run_prompt(),
+            1 => interpreter.run_file(&args[0]),
+            _ => {
+                println!("{}", USAGE);
+                return 64;
+            }
+        };
+        // like sysexits: 65 for programs which don't compile, 70 for errors while running
+        match err.map(|err| err.err_type) {
+            None => 0,
+            Some(ErrType::Exit(code)) => code,
+            Some(ErrType::ScanningErr | ErrType::TokenizingErr | ErrType::ParsingErr) => 65,
+            Some(_) => 70,
         }
-    };
+    });
+    std::process::exit(code)
+}
+
+fn unknown_option(flag: &str) -> ! {
+    println!("Unknown option {}\n{}", flag, USAGE);
+    std::process::exit(64)
+}
+
+/// Runs the annotated scripts in `dir`, exiting with 1 if any of them failed.
+fn run_tests(dir: &Path) {
+    match conformance::run_dir(dir) {
+        Ok(report) => {
+            println!("{}", report);
+            if !report.failures.is_empty() {
+                std::process::exit(1)
+            }
+        }
+        Err(err) => {
+            println!("Could not run tests in {}: {}", dir.display(), err);
+            std::process::exit(74)
+        }
+    }
+}
+
+/// Grants the capabilities given by `--allow-*` flags. Returns the first flag which isn't one.
+fn parse_capabilities(flags: &[String]) -> Result<Capabilities, String> {
+    let mut capabilities = Capabilities::none();
+    for flag in flags {
+        capabilities = match flag.split_once('=') {
+            Some(("--allow-read", dir)) => capabilities.allow_read(dir),
+            Some(("--allow-write", dir)) => capabilities.allow_write(dir),
+            Some(_) => return Err(flag.clone()),
+            None => match flag.as_str() {
+                "--allow-read" => capabilities.allow(Capability::FsRead),
+                "--allow-write" => capabilities.allow(Capability::FsWrite),
+                "--allow-env" => capabilities.allow(Capability::Env),
+                "--allow-clock" => capabilities.allow(Capability::Clock),
+                "--allow-exit" => capabilities.allow(Capability::Exit),
+                "--allow-stdin" => capabilities.allow(Capability::Stdin),
+                "--allow-all" => Capabilities::all(),
+                _ => return Err(flag.clone()),
+            },
+        };
+    }
+    Ok(capabilities)
+}
+
+/// Picks the format of the last `--error-format` flag. Returns the first flag which isn't valid.
+fn parse_error_format(flags: &[String]) -> Result<ErrorFormat, String> {
+    let mut format = ErrorFormat::Human;
+    for flag in flags {
+        format = match flag.as_str() {
+            "--error-format=human" => ErrorFormat::Human,
+            "--error-format=json" => ErrorFormat::Json,
+            _ => return Err(flag.clone()),
+        };
+    }
+    Ok(format)
 }
 
 #[cfg(test)]
 mod tests {
+    use super::interpreter::errors::{ErrType, ErrorFormat};
+    use super::interpreter::errors::LoxResult;
+    use super::interpreter::execute::{Capabilities, Capability, Ctx, HostObject, LoxObj};
+    use super::interpreter::conformance;
+    use super::interpreter::output::OutputBuffer;
+    use super::interpreter::parser::Parser;
+    use super::interpreter::scanner::Scanner;
+    use super::interpreter::tokens::{Equals, LoxValue, Punct, TokenValue};
+    use super::interpreter::value::Value;
+    use std::cell::Cell;
+    use std::collections::HashMap;
+    use std::path::Path;
+    use std::rc::Rc;
+    use super::interpreter::with_large_stack;
     use super::LoxInterpreter;
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::time::Duration;
 
     #[test]
     fn test_print() {
-        let mut interpreter = LoxInterpreter::new();
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
         let result = interpreter.run_file(&"./src/integration_tests/test_print.js".to_string());
-        assert_eq!(result.is_none(), true)
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "String(\"fsadf\")\n",
+                "Int(3)\n",
+                "Bool(true)\n",
+                "Int(3)\n",
+                "String(\"fsdaf\")\n",
+                "Bool(false)\n",
+                "Int(6)\n",
+                "Int(-3229)\n",
+            ]
+            .concat()
+        )
     }
 
     #[test]
@@ -58,10 +180,1086 @@ mod tests {
         assert_eq!(result.is_none(), true)
     }
 
+    #[test]
+    fn test_parameter_scope() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_parameter_scope.js".to_string());
+        assert_eq!(result.is_none(), true);
+        // the parameter shadows the outer `n` instead of overwriting it
+        assert_eq!(output.contents(), "Int(2)\nInt(1)\n")
+    }
+
     #[test]
     fn test_class() {
         let mut interpreter = LoxInterpreter::new();
         let result = interpreter.run_file(&"./src/integration_tests/test_class.js".to_string());
         assert_eq!(result.is_none(), true)
     }
+
+    #[test]
+    fn test_loop_control() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_loop_control.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(25)\n",
+                "Int(3)\n",
+                "Int(4)\n",
+            ]
+            .concat()
+        )
+    }
+
+    #[test]
+    fn test_break_outside_loop() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_break_outside_loop.js".to_string());
+        assert_eq!(result.is_some(), true)
+    }
+
+    #[test]
+    fn test_block() {
+        let mut interpreter = LoxInterpreter::new();
+        let result = interpreter.run_file(&"./src/integration_tests/test_block.js".to_string());
+        assert_eq!(result.is_none(), true)
+    }
+
+    #[test]
+    fn test_block_scope() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_block_scope.js".to_string());
+        assert_eq!(result.is_some(), true)
+    }
+
+    #[test]
+    fn test_semicolons() {
+        let mut interpreter = LoxInterpreter::new();
+        let result = interpreter.run_file(&"./src/integration_tests/test_semicolons.js".to_string());
+        assert_eq!(result.is_none(), true)
+    }
+
+    #[test]
+    fn test_missing_semicolon() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_missing_semicolon.js".to_string());
+        assert_eq!(result.map(|err| (err.pos().line, err.pos().line_pos)), Some((1, 13)))
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_parse_errors.js".to_string());
+        assert_eq!(result.map(|err| (err.pos().line, err.pos().line_pos)), Some((0, 10)));
+
+        let contents = output.contents();
+        let reported: Vec<&str> = contents
+            .lines()
+            .filter(|line| line.starts_with(" | ["))
+            .map(|line| line.split(']').next().unwrap())
+            .collect();
+        assert_eq!(reported, vec![" | [1", " | [3", " | [5", " | [8"]);
+        assert!(!contents.contains("Int(3)"));
+    }
+
+    #[test]
+    fn test_mixed_errors() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_mixed_errors.js".to_string());
+        assert!(matches!(result.map(|err| err.err_type), Some(ErrType::ScanningErr)));
+
+        let contents = output.contents();
+        let messages: Vec<&str> = contents
+            .lines()
+            .filter(|line| line.contains('^'))
+            .map(|line| line.trim_start_matches([' ', '|', '^']))
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unrecognized character '@'",
+                "Expected an expression, but found Punct(Semicolon).",
+                "Unrecognized character '#'",
+                "Expected Semicolon after statement, but found Kwd(Print).",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_expected_token_message() {
+        let mut interpreter = LoxInterpreter::new();
+        let err = interpreter.eval("var = 3;").map(|_| ()).unwrap_err();
+        assert_eq!(err.msg, "Expected \"identifier\", but found Punct(Equal).");
+    }
+
+    #[test]
+    fn test_object_names() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        interpreter
+            .eval("class P { var x = 1; } fun f() { return 1; } print P; print P(); print f;")
+            .unwrap();
+        assert_eq!(output.contents(), "P\nP instance\nFunction f\n");
+        let err = interpreter.eval("P(1, 2);").map(|_| ()).unwrap_err();
+        assert_eq!(err.msg, "P has 1 fields, but got 2 arguments");
+    }
+
+    #[test]
+    fn test_partial_program() {
+        let source = std::fs::read_to_string("./src/integration_tests/test_parse_errors.js").unwrap();
+        let tokens = Scanner::new(source).scan();
+        let parsed = Parser::new(tokens).parse();
+        assert_eq!(parsed.errors.len(), 4);
+        assert_eq!(parsed.program.len(), 4);
+    }
+
+    #[test]
+    fn test_scanning_errors() {
+        let source =
+            std::fs::read_to_string("./src/integration_tests/test_scanning_errors.js").unwrap();
+        let scanned = Scanner::new(source).scan();
+        let messages: Vec<&str> = scanned.errors.iter().map(|err| err.msg.as_str()).collect();
+        assert_eq!(
+            messages,
+            vec![
+                "Unrecognized character '@'",
+                "Invalid number literal 12ab",
+                "Unrecognized character '#'",
+                "Number 99999 does not fit into an integer",
+                "Unterminated string",
+            ]
+        );
+        let error_tokens = scanned
+            .tokens
+            .iter()
+            .filter(|t| matches!(t.val, TokenValue::Error(_)))
+            .count();
+        assert_eq!(error_tokens, 5);
+        assert!(scanned.tokens.last().is_some_and(|t| t.equals(Punct::Eof)));
+    }
+
+    #[test]
+    fn test_lists() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_lists.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "[Int(1), Int(2), Int(3)]\n",
+                "Int(1)\n",
+                "Int(3)\n",
+                "Int(-2)\n",
+                "[Int(1), Int(20), Int(30)]\n",
+                "Int(4)\n",
+                "Int(4)\n",
+                "[Int(1), Int(20), Int(30)]\n",
+                "[Int(0), Int(1), Int(20), Int(25), Int(30)]\n",
+                "Int(1)\n",
+                "[Int(20), Int(25)]\n",
+                "[Int(0), Int(20), Int(25), Int(30)]\n",
+                "Bool(true)\n",
+                "Bool(false)\n",
+                "Int(4)\n",
+                "Int(0)\n",
+                "String(\"a, b, c\")\n",
+                "Int(7)\n",
+                "Int(10)\n",
+            ]
+            .concat()
+        )
+    }
+
+    #[test]
+    fn test_list_out_of_range() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_list_out_of_range.js".to_string());
+        assert_eq!(result.map(|err| (err.pos().line, err.pos().line_pos)), Some((1, 6)))
+    }
+
+    #[test]
+    fn test_maps() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_maps.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "{String(\"bob\"): Int(31), String(\"alice\"): Int(27)}\n",
+                "Int(27)\n",
+                // overwriting a key keeps its position
+                "{String(\"bob\"): Int(32), String(\"alice\"): Int(27), String(\"carol\"): Int(45)}\n",
+                "Int(3)\n",
+                "Bool(true)\nInt(32)\nBool(false)\n",
+                "[String(\"alice\"), String(\"carol\")]\n[Int(27), Int(45)]\n",
+                "String(\"one\")\nString(\"yes\")\nInt(2)\n",
+                "Int(1)\n",
+                "Int(2)\n",
+                "Int(5)\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_map_missing_key() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_map_missing_key.js".to_string());
+        assert_eq!(result.map(|err| (err.pos().line, err.pos().line_pos)), Some((1, 6)))
+    }
+
+    #[test]
+    fn test_span_underline() {
+        let mut interpreter = LoxInterpreter::new();
+        let source = "var xs = [1, 2, 3];\nprint xs[3];";
+        let err = interpreter.eval(source).map(|_| ()).unwrap_err();
+        assert_eq!((err.span.start.line_pos, err.span.end.line_pos), (6, 11));
+
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert!(msg.contains(" | [2] print xs[3];\n |           ^^^^^ "));
+    }
+
+    #[test]
+    fn test_source_map() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let path = "./src/integration_tests/test_list_out_of_range.js".to_string();
+        interpreter.run_file(&path);
+        assert!(output.contents().starts_with(&format!(" --> {}:2:7\n", path)));
+
+        // the error is rendered against the source the function was defined in
+        interpreter.eval("fun third(xs) {\n  return xs[2];\n}").unwrap();
+        let err = interpreter.eval("third([1]);").map(|_| ()).unwrap_err();
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert!(msg.starts_with(" --> <eval>:2:10\n |\n | [2]   return xs[2];\n"));
+        assert!(msg.contains(" |   in third, called at <eval>:1:1\n"));
+    }
+
+    #[test]
+    fn test_json_errors() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new()
+            .with_output(output.clone())
+            .with_error_format(ErrorFormat::Json);
+        let path = "./src/integration_tests/test_map_missing_key.js";
+        interpreter.run_file(&path.to_string());
+        assert_eq!(
+            output.contents(),
+            [
+                r#"{"version":1,"severity":"error","type":"RuntimeError","#,
+                r#""message":"Key String(\"b\") is not in the map","while":"indexing","#,
+                r#""file":"./src/integration_tests/test_map_missing_key.js","#,
+                r#""span":{"start":{"line":2,"column":7,"offset":24},"#,
+                r#""end":{"line":2,"column":13,"offset":30}},"stack":[]}"#,
+                "\n",
+            ]
+            .concat()
+        );
+
+        interpreter.eval("fun third(xs) {\n  return xs[2];\n}").unwrap();
+        let err = interpreter.eval("third([1]);").map(|_| ()).unwrap_err();
+        let json = err.to_json(interpreter.sources());
+        assert!(json.ends_with(concat!(
+            r#""stack":[{"function":"third","file":"<eval>","#,
+            r#""span":{"start":{"line":1,"column":1,"offset":0},"#,
+            r#""end":{"line":1,"column":11,"offset":10}}}]}"#
+        )));
+
+        let err = interpreter.call_function("missing", vec![]).unwrap_err();
+        assert!(err.to_json(interpreter.sources()).contains(r#""file":null,"span":null"#));
+    }
+
+    #[test]
+    fn test_errors_without_location() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new()
+            .with_output(output.clone())
+            .with_error_format(ErrorFormat::Json);
+        let err = interpreter.run_file(&"./src/integration_tests/missing.js".to_string()).unwrap();
+        assert!(err.span.is_none());
+        assert!(output.contents().contains(r#""file":null,"span":null"#));
+
+        let err = interpreter.call_function("missing", vec![]).unwrap_err();
+        assert!(err.span.is_none());
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert_eq!(msg, " |\n | No global function missing\n");
+
+        interpreter.eval("fun fail() { return nil + 1; }").unwrap();
+        let err = interpreter.call_function("fail", vec![]).unwrap_err();
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert!(msg.ends_with(" |   in fail, called by the host\n"));
+    }
+
+    #[test]
+    fn test_diagnostics() {
+        let output = OutputBuffer::new();
+        let diagnostics = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new()
+            .with_output(output.clone())
+            .with_diagnostics(diagnostics.clone())
+            .with_error_format(ErrorFormat::Json);
+        let path = "./src/integration_tests/conformance/runtime_error.js";
+        let result = interpreter.run_file(&path.to_string());
+        assert!(matches!(result.map(|err| err.err_type), Some(ErrType::RuntimeError)));
+        assert_eq!(output.contents(), "Int(1)\n");
+        assert_eq!(
+            diagnostics.contents(),
+            [
+                r#"{"version":1,"severity":"error","type":"RuntimeError","#,
+                r#""message":"Index 2 is out of range for list of length 2","while":"indexing","#,
+                r#""file":"./src/integration_tests/conformance/runtime_error.js","#,
+                r#""span":{"start":{"line":3,"column":7,"offset":55},"#,
+                r#""end":{"line":3,"column":12,"offset":60}},"stack":[]}"#,
+                "\n",
+            ]
+            .concat()
+        );
+    }
+
+    #[test]
+    fn test_multiline_span() {
+        let mut interpreter = LoxInterpreter::new();
+        let source = "print 1 +\n  true;";
+        let err = interpreter.eval(source).map(|_| ()).unwrap_err();
+        assert_eq!((err.span.start.line, err.span.end.line), (0, 1));
+
+        let msg = err.generate_err_msg(interpreter.sources());
+        let underlines: Vec<&str> = msg.lines().filter(|line| line.contains('^')).collect();
+        assert_eq!(underlines.len(), 2);
+        assert!(underlines[0].ends_with("      ^^^"));
+        assert!(underlines[1].starts_with(" |       ^^^^ "));
+    }
+
+    #[test]
+    fn test_for_in() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_for_in.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(6)\n",
+                "String(\"a\")\nString(\"b\")\n",
+                "String(\"h\")\nString(\"e\")\nString(\"y\")\n",
+                "Int(0)\nInt(3)\nInt(6)\nInt(9)\n",
+                "Int(3)\nInt(2)\nInt(1)\n",
+                // items pushed while iterating are visited as well
+                "Int(1)\nInt(2)\nInt(3)\n",
+                "[Int(0), Int(0)]\n[Int(1), Int(0)]\n",
+                "Int(3)\nInt(2)\nInt(1)\n",
+                "Int(2)\nInt(1)\n",
+                "String(\"x\")\nString(\"y\")\n",
+                "Int(7)\nInt(8)\nNil\n",
+                "Bool(true)\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_object_equality() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_object_equality.js".to_string());
+        assert_eq!(result.is_none(), true);
+        // objects are compared by identity
+        assert_eq!(
+            output.contents(),
+            [
+                "Bool(true)\nBool(false)\nBool(true)\n",
+                "Bool(true)\nBool(false)\n",
+                "Bool(true)\nBool(false)\nBool(false)\nBool(true)\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_cyclic_print() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let err = interpreter.run_file(&"./src/integration_tests/test_cyclic_print.js".to_string());
+        assert_eq!(err.unwrap().msg, "\"[Int(1), [...]]\" is not \"a value\".");
+        let map = "{String(\"a\"): Int(1), String(\"self\"): {...}}";
+        assert!(output.contents().starts_with(
+            &[
+                "[Int(1), [...]]\n".to_string(),
+                format!("{}\n", map),
+                format!("[{}, {}]\n", map, map),
+            ]
+            .join("")
+        ))
+    }
+
+    #[test]
+    fn test_generators() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_generators.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(1)\nInt(2)\nInt(3)\n",
+                // finished generators keep returning nil
+                "Int(1)\nInt(2)\nNil\nNil\n",
+                "Int(2)\nInt(4)\nInt(6)\nString(\"done\")\n",
+                "Int(0)\nInt(2)\nInt(3)\n",
+                "[Int(0), Int(0)]\n[Int(0), Int(1)]\n[Int(1), Int(0)]\n[Int(1), Int(1)]\n",
+                "Int(30)\nInt(10)\nInt(20)\n",
+                "[Int(0), Int(1), Int(2), Int(3), Int(4)]\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_generator_try() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_generator_try.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(1)\nString(\"zero\")\nInt(2)\n",
+                "[Int(1), Int(0), Int(2)]\n",
+                "Int(1)\nString(\"finally\")\n",
+                "String(\"a\")\nString(\"after\")\n",
+                "[Int(1), Int(0), Int(2), String(\"inner\"), String(\"outer\")]\n",
+                "Int(1)\nString(\"LogicError\")\nString(\"again\")\n",
+                "[Int(1), Int(0), Int(2), String(\"inner\"), String(\"outer\"), String(\"failing\")]\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_exceptions.js".to_string());
+        assert_eq!(result.is_none(), true);
+        // nothing after a `throw` runs, so "You should NOT see this" is missing
+        assert_eq!(
+            output.contents(),
+            [
+                "String(\"boom\")\n",
+                "String(\"Index 5 is out of range for list of length 2\")\n",
+                "String(\"RuntimeError\")\nInt(10)\n",
+                "String(\"LogicError\")\n",
+                "Int(42)\n",
+                "String(\"from try\")\n[String(\"cleaned\")]\n",
+                "String(\"from finally\")\n",
+                "String(\"inner\")\n[String(\"cleaned\"), String(\"inner finally\")]\n",
+                "[String(\"first\"), String(\"second\")]\n",
+                "[String(\"cleaned\"), String(\"inner finally\"), Int(1), Int(2), Int(3)]\n",
+                "String(\"custom\")\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_uncaught_exception() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_uncaught_exception.js".to_string());
+        let err = result.expect("the exception should not be caught");
+        assert!(matches!(err.err_type, ErrType::Exception));
+        assert_eq!(
+            err.thrown.map(|value| value.to_string()),
+            Some("String(\"nobody catches this\")".to_string())
+        )
+    }
+
+    #[test]
+    fn test_stack_trace() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_stack_trace.js".to_string());
+        let trace: Vec<(String, u32)> = result
+            .map(|err| err.stack)
+            .unwrap_or_default()
+            .into_iter()
+            .map(|frame| (frame.function, frame.call_site.start.line))
+            .collect();
+        assert_eq!(
+            trace,
+            vec![
+                ("start".to_string(), 11),
+                ("countdown".to_string(), 8),
+                ("countdown".to_string(), 4),
+                ("countdown".to_string(), 4),
+            ]
+        )
+    }
+
+    #[test]
+    fn test_stack_overflow() {
+        // errors can hold Lox values, which stay on the interpreter's thread
+        let finished = with_large_stack(|| {
+            let mut interpreter = LoxInterpreter::new();
+            interpreter
+                .run_file(&"./src/integration_tests/test_stack_overflow.js".to_string())
+                .is_none()
+        });
+        assert_eq!(finished, true)
+    }
+
+    #[test]
+    fn test_stack_overflow_on_small_stack() {
+        let finished = std::thread::Builder::new()
+            .stack_size(256 * 1024)
+            .spawn(|| {
+                let mut interpreter = LoxInterpreter::new();
+                interpreter
+                    .run_file(&"./src/integration_tests/test_stack_overflow.js".to_string())
+                    .is_none()
+            })
+            .unwrap()
+            .join()
+            .unwrap();
+        assert_eq!(finished, true)
+    }
+
+    #[test]
+    fn test_max_call_depth() {
+        let mut interpreter = LoxInterpreter::new().with_max_call_depth(20);
+        let result = interpreter
+            .run_file(&"./src/integration_tests/test_uncaught_stack_overflow.js".to_string());
+        let (err_type, depth) = result
+            .map(|err| (err.err_type, err.stack.len()))
+            .unwrap_or((ErrType::LogicError, 0));
+        assert!(matches!(err_type, ErrType::RuntimeError));
+        assert_eq!(depth, 20)
+    }
+
+    #[test]
+    fn test_step_limit() {
+        let mut interpreter = LoxInterpreter::new().with_step_limit(1000);
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_infinite_loop.js".to_string());
+        assert!(matches!(
+            result.map(|err| err.err_type),
+            Some(ErrType::StepLimit)
+        ))
+    }
+
+    #[test]
+    fn test_timeout() {
+        let mut interpreter = LoxInterpreter::new().with_timeout(Duration::from_millis(50));
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_infinite_loop.js".to_string());
+        assert!(matches!(
+            result.map(|err| err.err_type),
+            Some(ErrType::Timeout)
+        ))
+    }
+
+    #[test]
+    fn test_cancel() {
+        let cancel = Arc::new(AtomicBool::new(false));
+        let canceller = cancel.clone();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(50));
+            canceller.store(true, Ordering::Relaxed);
+        });
+
+        let mut interpreter = LoxInterpreter::new().with_cancel_flag(cancel);
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_infinite_loop.js".to_string());
+        assert!(matches!(
+            result.map(|err| err.err_type),
+            Some(ErrType::Cancelled)
+        ))
+    }
+
+    #[test]
+    fn test_heap_stats() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_heap_stats.js".to_string());
+        assert_eq!(result.is_none(), true);
+
+        let stats = interpreter.heap_stats();
+        assert_eq!(
+            (stats.lists, stats.maps, stats.objects, stats.functions, stats.classes),
+            (2, 2, 1, 1, 1)
+        )
+    }
+
+    #[test]
+    fn test_out_of_memory() {
+        let mut interpreter = LoxInterpreter::new().with_memory_limit(100_000);
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_out_of_memory.js".to_string());
+        let (err_type, msg) = result
+            .map(|err| (err.err_type, err.msg))
+            .unwrap_or((ErrType::LogicError, String::new()));
+        assert!(matches!(err_type, ErrType::RuntimeError));
+        assert!(msg.starts_with("Out of memory"))
+    }
+
+    #[test]
+    fn test_out_of_memory_index() {
+        let mut interpreter = LoxInterpreter::new().with_memory_limit(100_000);
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_out_of_memory_index.js".to_string());
+        let (err_type, msg) = result
+            .map(|err| (err.err_type, err.msg))
+            .unwrap_or((ErrType::LogicError, String::new()));
+        assert!(matches!(err_type, ErrType::RuntimeError));
+        assert!(msg.starts_with("Out of memory"))
+    }
+
+    #[test]
+    fn test_out_of_memory_copies() {
+        // strings are copied into lists, maps and bindings, so each copy counts
+        let programs = [
+            "var xs = []; for (i in range(0, 1000, 1)) { push(xs, big); }",
+            "var xs = []; for (i in range(0, 1000, 1)) { insert(xs, 0, big); }",
+            "var m = {}; for (i in range(0, 1000, 1)) { m[i] = big; }",
+            "var xs = []; for (i in range(0, 1000, 1)) { var copy = big; push(xs, [copy]); }",
+        ];
+        for program in programs {
+            let mut interpreter = LoxInterpreter::new().with_memory_limit(1_000_000);
+            interpreter.set_global("big", "x".repeat(29_000)).unwrap();
+            let err = interpreter.eval(program).map(|_| ()).unwrap_err();
+            assert!(matches!(err.err_type, ErrType::RuntimeError));
+            assert!(err.msg.starts_with("Out of memory"), "{}: {}", program, err.msg);
+            assert!(interpreter.heap_stats().bytes < 2_000_000);
+        }
+    }
+
+    #[test]
+    fn test_capabilities() {
+        std::fs::create_dir_all("./target/lox_io_test").unwrap();
+        let capabilities = Capabilities::none()
+            .allow_read("./target/lox_io_test")
+            .allow_write("./target/lox_io_test");
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new()
+            .with_capabilities(capabilities)
+            .with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_capabilities.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "String(\"hello\")\n",
+                "String(\"permission denied: fs.read of Cargo.toml outside of the allowed directories\")\n",
+                "String(\"permission denied: env\")\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_exit() {
+        let output = OutputBuffer::new();
+        let capabilities = Capabilities::none().allow(Capability::Exit);
+        let mut interpreter = LoxInterpreter::new()
+            .with_capabilities(capabilities)
+            .with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_exit.js".to_string());
+        assert!(matches!(result.map(|err| err.err_type), Some(ErrType::Exit(3))));
+        // `exit` is neither caught nor reported
+        assert_eq!(output.contents(), "String(\"before\")\n")
+    }
+
+    #[test]
+    fn test_permission_denied() {
+        let mut interpreter = LoxInterpreter::new();
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_permission_denied.js".to_string());
+        assert_eq!(
+            result.map(|err| err.msg),
+            Some("permission denied: fs.write".to_string())
+        )
+    }
+
+    #[test]
+    fn test_modulo() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result = interpreter.run_file(&"./src/integration_tests/test_modulo.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(1)\n",
+                "String(\"Division by zero in mod of 1 and 0\")\n",
+                "String(\"Integer overflow in mod of -32768 and -1\")\n",
+                "Int(0)\n",
+            ]
+            .concat()
+        )
+    }
+
+    #[test]
+    fn test_native_functions() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        register_host_fixtures(&mut interpreter);
+
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_native_functions.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(
+            output.contents(),
+            [
+                "Int(1)\n",
+                "Int(42)\n",
+                "Int(8)\n",
+                "Int(1)\n",
+                "String(\"double expects 1 arguments, but got 2\")\n",
+            ]
+            .join("")
+        )
+    }
+
+    #[test]
+    fn test_native_capability() {
+        let beeps = Rc::new(Cell::new(0));
+        let register = |interpreter: &mut LoxInterpreter| {
+            let beeps = beeps.clone();
+            interpreter.register_native_with_capability("beep", 0, Capability::Stdin, move |_, _| {
+                beeps.set(beeps.get() + 1);
+                Ok(LoxObj::Plain(LoxValue::Nil))
+            });
+        };
+
+        let mut interpreter = LoxInterpreter::new();
+        register(&mut interpreter);
+        let err = interpreter.eval("beep();").map(|_| ()).unwrap_err();
+        assert_eq!(err.msg, "permission denied: stdin");
+        assert_eq!(beeps.get(), 0);
+
+        let capabilities = Capabilities::none().allow(Capability::Stdin);
+        let mut interpreter = LoxInterpreter::new().with_capabilities(capabilities);
+        register(&mut interpreter);
+        interpreter.eval("beep();").unwrap();
+        assert_eq!(beeps.get(), 1)
+    }
+
+    #[test]
+    fn test_eval() {
+        let mut interpreter = LoxInterpreter::new();
+        interpreter.set_global("base", 40).unwrap();
+        let value = interpreter.eval("var answer = base + 2; answer;");
+        assert_eq!(value.unwrap(), Value::Int(42));
+        assert_eq!(interpreter.get_global("answer"), Some(Value::Int(42)));
+        assert_eq!(interpreter.eval("var unused = 1;").unwrap(), Value::Nil);
+
+        let err = interpreter.eval("missing;").map(|_| ()).unwrap_err();
+        assert!(matches!(err.err_type, ErrType::RuntimeError));
+        assert!(interpreter.set_global("huge", 100_000_i64).is_err())
+    }
+
+    #[test]
+    fn test_eval_drops_sources() {
+        let mut interpreter = LoxInterpreter::new();
+        interpreter.eval("fun fail(n) { return n + nil; }").unwrap();
+        for n in 0..100 {
+            interpreter.eval(&format!("var x = {};", n)).unwrap();
+        }
+        assert_eq!(interpreter.sources().len(), 2);
+
+        let err = interpreter.eval("fail(1);").map(|_| ()).unwrap_err();
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert!(msg.contains("[1] fun fail(n) { return n + nil; }"));
+        assert!(msg.contains("in fail, called at <eval>:1:1"));
+        interpreter.eval("x;").unwrap();
+        let msg = err.generate_err_msg(interpreter.sources());
+        assert!(msg.contains("[1] fun fail(n) { return n + nil; }"));
+        assert!(msg.contains("in fail, called at line 1"));
+    }
+
+    #[test]
+    fn test_call_function() {
+        let mut interpreter = LoxInterpreter::new();
+        interpreter
+            .eval("fun describe(name, tags) { return {\"name\": name, \"tags\": tags}; }")
+            .unwrap();
+
+        let tags = vec![Value::from("fast"), Value::from(true)];
+        let result = interpreter.call_function("describe", vec!["lox".into(), tags.into()]);
+        let described = HashMap::<String, Value>::try_from(result.unwrap()).unwrap();
+        assert_eq!(described["name"], Value::from("lox"));
+        assert_eq!(
+            Vec::<Value>::try_from(described["tags"].clone()).unwrap(),
+            vec![Value::String("fast".to_string()), Value::Bool(true)]
+        );
+
+        let len = interpreter.call_function("len", vec![vec![1, 2, 3].into()]);
+        assert_eq!(i64::try_from(len.unwrap()).unwrap(), 3);
+        assert!(interpreter.call_function("nothing", Vec::new()).is_err())
+    }
+
+    #[test]
+    fn test_deep_recursion_from_host() {
+        // runs on the test thread, without `with_large_stack`
+        let mut interpreter = LoxInterpreter::new();
+        let err = interpreter
+            .eval("fun f(n) { return f(n + 1); } f(0);")
+            .map(|_| ())
+            .unwrap_err();
+        assert!(matches!(err.err_type, ErrType::RuntimeError));
+        assert!(err.msg.starts_with("Stack overflow"));
+
+        interpreter
+            .eval("fun depth(n) { if (n == 0) { return 0; } return depth(n - 1) + 1; }")
+            .unwrap();
+        let depth = interpreter.call_function("depth", vec![Value::Int(3000)]);
+        assert_eq!(depth.unwrap(), Value::Int(3000))
+    }
+
+    struct Counter {
+        count: Cell<i16>,
+    }
+
+    impl HostObject for Counter {
+        fn type_name(&self) -> &str {
+            "Counter"
+        }
+
+        fn get_property(&self, ctx: &mut Ctx, name: &str) -> LoxResult<LoxObj> {
+            match name {
+                "count" => Ok(LoxObj::Plain(LoxValue::Integer(self.count.get()))),
+                _ => Err(ctx.error(format!("Counter has no property {}", name))),
+            }
+        }
+
+        fn set_property(&self, ctx: &mut Ctx, name: &str, value: LoxObj) -> LoxResult<()> {
+            match (name, value) {
+                ("count", LoxObj::Plain(LoxValue::Integer(n))) => {
+                    self.count.set(n);
+                    Ok(())
+                }
+                _ => Err(ctx.error(format!("Cannot set property {} on Counter", name))),
+            }
+        }
+
+        fn call_method(&self, ctx: &mut Ctx, name: &str, args: Vec<LoxObj>) -> LoxResult<LoxObj> {
+            match (name, args.as_slice()) {
+                ("increment", [LoxObj::Plain(LoxValue::Integer(by))]) => {
+                    self.count.set(self.count.get() + by);
+                    Ok(LoxObj::Plain(LoxValue::Nil))
+                }
+                _ => Err(ctx.error(format!("Counter has no method {}", name))),
+            }
+        }
+
+        fn display(&self) -> String {
+            format!("Counter({})", self.count.get())
+        }
+    }
+
+    /// Registers the `double` and `apply` natives and the `counter` host object, which the
+    /// native function and host object scripts rely on. Returns the counter.
+    fn register_host_fixtures(interpreter: &mut LoxInterpreter) -> Rc<Counter> {
+        interpreter.register_native("double", 1, |ctx, args| match &args[0] {
+            LoxObj::Plain(LoxValue::Integer(n)) => Ok(LoxObj::Plain(LoxValue::Integer(n * 2))),
+            other => Err(ctx.error(format!("Can't double {}", other.to_string()))),
+        });
+        interpreter.register_native("apply", 2, |ctx, args| {
+            ctx.call(&args[0], vec![args[1].clone()])
+        });
+        let counter = Rc::new(Counter {
+            count: Cell::new(1),
+        });
+        interpreter
+            .set_global("counter", Value::Object(LoxObj::Host(counter.clone())))
+            .unwrap();
+        counter
+    }
+
+    #[test]
+    fn test_host_objects() {
+        let mut interpreter = LoxInterpreter::new();
+        let counter = register_host_fixtures(&mut interpreter);
+
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_host_objects.js".to_string());
+        assert_eq!(result.is_none(), true);
+        assert_eq!(counter.count.get(), 10)
+    }
+
+    #[test]
+    fn test_error_report_output() {
+        let output = OutputBuffer::new();
+        let mut interpreter = LoxInterpreter::new().with_output(output.clone());
+        let result =
+            interpreter.run_file(&"./src/integration_tests/test_missing_semicolon.js".to_string());
+        assert_eq!(result.is_some(), true);
+        assert!(output
+            .contents()
+            .contains("Expected Semicolon after statement"))
+    }
+
+    #[test]
+    fn test_conformance() {
+        let report =
+            conformance::run_dir(Path::new("./src/integration_tests/conformance")).unwrap();
+        assert_eq!(report.failures.len(), 0, "{}", report);
+        assert_eq!(report.passed, 8)
+    }
+
+    #[test]
+    fn test_integration_scripts() {
+        std::fs::create_dir_all("./target/lox_io_test").unwrap();
+        // everything the scripts rely on, with limits loose enough for all but the runaway ones
+        let setup = |interpreter: LoxInterpreter| {
+            let capabilities = Capabilities::none()
+                .allow_read("./target/lox_io_test")
+                .allow_write("./target/lox_io_test")
+                .allow(Capability::Exit);
+            let mut interpreter = interpreter
+                .with_capabilities(capabilities)
+                .with_step_limit(100_000)
+                .with_memory_limit(100_000);
+            register_host_fixtures(&mut interpreter);
+            interpreter
+        };
+
+        let report =
+            conformance::run_dir_with(Path::new("./src/integration_tests"), &setup).unwrap();
+        assert_eq!(report.failures.len(), 0, "{}", report);
+        assert_eq!(report.passed, 48)
+    }
+
+    #[test]
+    fn test_conformance_diff() {
+        let report =
+            conformance::run_dir(Path::new("./src/conformance_failing"))
+                .unwrap();
+        let diffs: Vec<Vec<String>> = report.failures.into_iter().map(|f| f.diff).collect();
+        assert_eq!(
+            diffs,
+            vec![vec![
+                "output line 2:".to_string(),
+                "- Int(3)".to_string(),
+                "+ Int(2)".to_string(),
+                "output line 3:".to_string(),
+                "+ Int(4)".to_string(),
+            ]]
+        )
+    }
+
+    /// Pieces of Lox spliced into the corpus, chosen to hit the edges of the grammar.
+    const FRAGMENTS: &[&str] = &[
+        "(", ")", "{", "}", "[", "]", ",", ";", ".", ":", "=", "==", "!", "-", "+", "*", "/", "%",
+        "<", ">=", "\"", "1 2", "32767", "99999", "-32768", "nil", "true", "this", "var", "fun",
+        "class", "return", "yield", "throw", "try", "catch", "finally", "while", "for", "in",
+        "break", "continue", "if", "else", "print", "outer:", "x", "f(", "\n", " ", "@", "#",
+        "mod(1, 0)", "mod(0 - 32767 - 1, 0 - 1)",
+    ];
+
+    /// Xorshift, so every run mutates the corpus the same way.
+    struct Rng(u64);
+
+    impl Rng {
+        fn below(&mut self, bound: usize) -> usize {
+            self.0 ^= self.0 << 13;
+            self.0 ^= self.0 >> 7;
+            self.0 ^= self.0 << 17;
+            (self.0 % bound.max(1) as u64) as usize
+        }
+    }
+
+    fn read_corpus(dir: &Path, programs: &mut Vec<String>) {
+        let mut entries: Vec<_> = std::fs::read_dir(dir).unwrap().map(|e| e.unwrap().path()).collect();
+        entries.sort();
+        for path in entries {
+            if path.is_dir() {
+                read_corpus(&path, programs);
+            } else if path.extension().is_some_and(|ext| ext == "js") {
+                programs.push(std::fs::read_to_string(path).unwrap());
+            }
+        }
+    }
+
+    fn mutate(rng: &mut Rng, program: &str, corpus: &[String]) -> String {
+        let mut chars: Vec<char> = program.chars().collect();
+        for _ in 0..1 + rng.below(4) {
+            let at = rng.below(chars.len() + 1);
+            let len = rng.below(12).min(chars.len() - at);
+            match rng.below(4) {
+                0 => {
+                    chars.drain(at..at + len);
+                }
+                1 => {
+                    let fragment = FRAGMENTS[rng.below(FRAGMENTS.len())];
+                    chars.splice(at..at, fragment.chars());
+                }
+                2 => {
+                    let copied: Vec<char> = chars[at..at + len].to_vec();
+                    chars.splice(at..at, copied);
+                }
+                _ => {
+                    let other: Vec<char> = corpus[rng.below(corpus.len())].chars().collect();
+                    let from = rng.below(other.len());
+                    let to = (from + rng.below(80)).min(other.len());
+                    chars.splice(at..at + len, other[from..to].iter().cloned());
+                }
+            }
+        }
+        chars.into_iter().collect()
+    }
+
+    /// Runs the program with tight limits, and renders its error like the CLI would.
+    fn run_mutated(program: &str) {
+        let mut interpreter = LoxInterpreter::new()
+            .with_output(OutputBuffer::new())
+            .with_max_call_depth(50)
+            .with_step_limit(2_000)
+            .with_memory_limit(1_000_000)
+            .with_timeout(Duration::from_secs(1));
+        if let Err(err) = interpreter.eval(program) {
+            err.generate_err_msg(interpreter.sources());
+        }
+    }
+
+    #[test]
+    fn test_no_panics() {
+        let mut corpus = Vec::new();
+        read_corpus(Path::new("./src/integration_tests"), &mut corpus);
+
+        let panicked = with_large_stack(move || {
+            let mut rng = Rng(0x2545_f491_4f6c_dd1d);
+            let mut panicked = Vec::new();
+            for i in 0..3_000 {
+                let program = mutate(&mut rng, &corpus[i % corpus.len()], &corpus);
+                let outcome =
+                    std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| run_mutated(&program)));
+                if outcome.is_err() {
+                    panicked.push(program);
+                }
+            }
+            panicked
+        });
+        assert!(panicked.is_empty(), "{} programs panicked, e.g.:\n{}", panicked.len(), panicked[0]);
+    }
 }